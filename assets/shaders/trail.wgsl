@@ -0,0 +1,56 @@
+// Import the standard 2d mesh uniforms and set their bind groups
+#import bevy_sprite::mesh2d_view_bind_group
+#import bevy_sprite::mesh2d_struct
+
+[[group(0), binding(0)]] var<uniform> view: View;
+[[group(2), binding(0)]] var<uniform> mesh: Mesh2d;
+
+// Bindings specified in SpecializedMaterial2d::bind_group_layout
+struct TrailMaterial {
+    color: vec4<f32>;
+    fade: f32;
+};
+[[group(1), binding(0)]] var<uniform> material: TrailMaterial;
+
+// The structure of the vertex buffer is as specified in `specialize()`
+struct Vertex {
+    [[location(0)]] position: vec2<f32>;
+    [[location(1)]] uv: vec2<f32>;
+};
+struct VertexOutput {
+    // The vertex shader must set the on-screen position of the vertex
+    [[builtin(position)]] clip_position: vec4<f32>;
+
+    // Passed along to the fragment shader
+    [[location(0)]] uv: vec2<f32>;
+};
+
+/// Entry point for the vertex shader
+[[stage(vertex)]]
+fn vertex(vertex: Vertex) -> VertexOutput {
+    var out: VertexOutput;
+
+    // The ribbon vertices are relative to its owner, which the model matrix of the ribbon follows
+    out.clip_position = view.view_proj * mesh.model * vec4<f32>(vertex.position, 0.0, 1.0);
+    out.uv = vertex.uv;
+
+    return out;
+}
+
+// The input of the fragment shader must correspond to the output of the vertex shader for all `location`s
+struct FragmentInput {
+    // x goes from 0 to 1 along the ribbon, y from 0 to 1 across it
+    [[location(0)]] uv: vec2<f32>;
+};
+
+/// Entry point for the fragment shader
+[[stage(fragment)]]
+fn fragment(in: FragmentInput) -> [[location(0)]] vec4<f32> {
+    // Fade out towards the end of the ribbon
+    let along = 1. - in.uv.x * material.fade;
+
+    // Soften the edges of the ribbon
+    let across = 1. - pow(abs(in.uv.y * 2. - 1.), 4.0);
+
+    return vec4<f32>(material.color.rgb, material.color.a * along * across);
+}
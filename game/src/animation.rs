@@ -0,0 +1,140 @@
+use std::collections::HashMap;
+
+use bevy::prelude::*;
+use serde::Deserialize;
+
+use crate::{player::PlayerComponent, GameState, GameSystems};
+
+/// Plays sprite sheet animations, picking the animation of the bird from how it is flying
+pub struct AnimationPlugin;
+
+impl Plugin for AnimationPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_system(
+            select_bird_animation
+                .label(AnimationSystems::Select)
+                .after(GameSystems::PlayerMovement),
+        );
+        app.add_system(animate_sprites.after(AnimationSystems::Select));
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash, SystemLabel)]
+enum AnimationSystems {
+    Select,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Deserialize)]
+pub enum BirdAnimation {
+    Gliding,
+    /// Rising, beating the wings
+    Flapping,
+    /// Wings tucked in
+    Diving,
+    Sliding,
+    /// The bird is not flying at all, e.g. when the game is paused
+    Sleeping,
+}
+
+impl BirdAnimation {
+    fn from_player(player: &PlayerComponent, asleep: bool) -> Self {
+        if asleep {
+            BirdAnimation::Sleeping
+        } else if player.grounded {
+            BirdAnimation::Sliding
+        } else if player.diving {
+            BirdAnimation::Diving
+        } else if player.velocity.y > 0. {
+            BirdAnimation::Flapping
+        } else {
+            BirdAnimation::Gliding
+        }
+    }
+}
+
+/// A range of frames in a sprite sheet
+#[derive(Debug, Clone, Deserialize)]
+pub struct AnimationClip {
+    pub first: usize,
+    pub frames: usize,
+}
+
+/// Sprite sheet of equally sized frames, with the clip to play for each animation
+#[derive(Debug, Clone, Deserialize)]
+pub struct SpriteSheet {
+    pub texture: String,
+    pub tile_size: (f32, f32),
+    pub columns: usize,
+    pub rows: usize,
+    /// Frames per second
+    pub fps: f32,
+    pub clips: HashMap<BirdAnimation, AnimationClip>,
+}
+
+impl SpriteSheet {
+    pub fn texture_atlas(&self, asset_server: &AssetServer) -> TextureAtlas {
+        TextureAtlas::from_grid(
+            asset_server.load(self.texture.as_str()),
+            Vec2::new(self.tile_size.0, self.tile_size.1),
+            self.columns,
+            self.rows,
+        )
+    }
+}
+
+#[derive(Component)]
+pub struct SpriteAnimation {
+    clips: HashMap<BirdAnimation, AnimationClip>,
+    state: BirdAnimation,
+    frame: usize,
+    timer: Timer,
+}
+
+impl SpriteAnimation {
+    pub fn new(sheet: &SpriteSheet) -> Self {
+        Self {
+            clips: sheet.clips.clone(),
+            state: BirdAnimation::Gliding,
+            frame: 0,
+            timer: Timer::from_seconds(1. / sheet.fps, true),
+        }
+    }
+}
+
+// The animated sprite is a child of the player, see `skin.rs`
+fn select_bird_animation(
+    players: Query<(&PlayerComponent, &Children)>,
+    mut animations: Query<&mut SpriteAnimation>,
+    state: Res<State<GameState>>,
+) {
+    let asleep = state.current() != &GameState::Playing;
+
+    for (player, children) in players.iter() {
+        let state = BirdAnimation::from_player(player, asleep);
+
+        for &child in children.iter() {
+            if let Ok(mut animation) = animations.get_mut(child) {
+                if animation.state != state {
+                    animation.state = state;
+                    animation.frame = 0;
+                }
+            }
+        }
+    }
+}
+
+fn animate_sprites(
+    mut sprites: Query<(&mut SpriteAnimation, &mut TextureAtlasSprite)>,
+    time: Res<Time>,
+) {
+    for (mut animation, mut sprite) in sprites.iter_mut() {
+        animation.timer.tick(time.delta());
+        if animation.timer.just_finished() {
+            animation.frame += 1;
+        }
+
+        if let Some(clip) = animation.clips.get(&animation.state) {
+            sprite.index = clip.first + animation.frame % clip.frames.max(1);
+        }
+    }
+}
@@ -0,0 +1,68 @@
+use bevy::prelude::*;
+
+use crate::{
+    player::{PlayerComponent, GROUND_CLEARANCE, PHYSICS_STEP},
+    run::PERFECT_IMPACT,
+    terrain::Terrain,
+    tuning::GameTuning,
+};
+
+/// How far ahead the autopilot looks for the next landing, in physics steps
+const LOOKAHEAD_STEPS: u32 = 240;
+
+/// Decides when to dive by following where the bird would fall to, aiming for perfect slides.
+/// Used to demo the game from the title screen and as a player in [`crate::sim`].
+/// While it exists as a resource, `player_system` asks it whether to dive before every physics step
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Autopilot;
+
+impl Autopilot {
+    pub fn dive(
+        &self,
+        transform: &Transform,
+        player: &PlayerComponent,
+        terrain: &Terrain,
+        tuning: &GameTuning,
+    ) -> bool {
+        let position = transform.translation.truncate();
+
+        // Sliding down a hill gets faster by diving, sliding up it launches further by gliding
+        if player.grounded {
+            return terrain.normal(position.x).x > 0.;
+        }
+
+        let landing = |gravity| {
+            landing_impact(position, player.velocity, gravity, terrain, tuning)
+                .unwrap_or(f32::INFINITY)
+        };
+        let dive_impact = landing(tuning.dive_gravity);
+
+        // Diving all the way down is fastest, as long as it doesn't land any harder than gliding
+        dive_impact < PERFECT_IMPACT || dive_impact <= landing(tuning.gravity)
+    }
+}
+
+/// How hard the bird would hit the ground, falling from `position` with `velocity` under `gravity`.
+/// Follows the same steps as `step_player`, `None` if it doesn't land within the lookahead
+fn landing_impact(
+    mut position: Vec2,
+    mut velocity: Vec2,
+    gravity: f32,
+    terrain: &Terrain,
+    tuning: &GameTuning,
+) -> Option<f32> {
+    for _ in 0..LOOKAHEAD_STEPS {
+        velocity.y -= gravity * PHYSICS_STEP;
+        position += velocity * PHYSICS_STEP;
+
+        if position.y < terrain.height(position.x) + GROUND_CLEARANCE {
+            return Some((-velocity.dot(terrain.normal(position.x))).max(0.));
+        }
+
+        if position.y > tuning.ceiling && velocity.y > 0. {
+            velocity.y -= tuning.ceiling_drag * PHYSICS_STEP;
+        }
+    }
+
+    None
+}
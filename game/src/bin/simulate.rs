@@ -0,0 +1,156 @@
+//! Simulates runs over many seeds without a window, for checking the balance of the tuning.
+//! Prints how each run went as CSV or JSON, and the averages to stderr
+
+use std::path::PathBuf;
+
+use bevy_bird_lib::{
+    autopilot::Autopilot,
+    sim::{self, DivePolicy, DiveWhileFalling, Glide, RunSummary, Simulation},
+};
+
+const USAGE: &str = "\
+Usage: simulate [OPTIONS]
+
+Options:
+  --runs <N>           Number of seeds to simulate, 100 by default
+  --first-seed <SEED>  Seed of the first run, the others follow it, 0 by default
+  --policy <POLICY>    When the bird dives: glide never dives, falling dives while falling,
+                       autopilot aims for perfect slides, autopilot by default
+  --format <FORMAT>    csv or json, csv by default
+  --tuning <FILE>      Tuning to fly with, assets/game.tuning.ron by default
+  --help               Print this help";
+
+#[derive(Clone, Copy)]
+enum Policy {
+    Glide,
+    Falling,
+    Autopilot,
+}
+
+#[derive(Clone, Copy)]
+enum Format {
+    Csv,
+    Json,
+}
+
+struct Options {
+    runs: u32,
+    first_seed: u32,
+    policy: Policy,
+    format: Format,
+    tuning: PathBuf,
+}
+
+fn main() {
+    let options = match parse_args(std::env::args().skip(1)) {
+        Ok(options) => options,
+        Err(err) => {
+            eprintln!("{}\n\n{}", err, USAGE);
+            std::process::exit(2);
+        }
+    };
+
+    let tuning = sim::load_tuning(&options.tuning);
+    let summaries: Vec<RunSummary> = (0..options.runs)
+        .map(|run| {
+            let mut policy: Box<dyn DivePolicy> = match options.policy {
+                Policy::Glide => Box::new(Glide),
+                Policy::Falling => Box::new(DiveWhileFalling),
+                Policy::Autopilot => Box::new(Autopilot),
+            };
+
+            let mut sim = Simulation::new(options.first_seed.wrapping_add(run), tuning.clone());
+            sim.run_until_sunset(policy.as_mut());
+            sim.summary()
+        })
+        .collect();
+
+    match options.format {
+        Format::Csv => {
+            println!("seed,score,distance,landings,perfect_slides,bad_landings");
+            for summary in &summaries {
+                println!(
+                    "{},{},{:.1},{},{},{}",
+                    summary.seed,
+                    summary.score,
+                    summary.distance,
+                    summary.landings,
+                    summary.perfect_slides,
+                    summary.bad_landings
+                );
+            }
+        }
+        Format::Json => {
+            let json =
+                serde_json::to_string_pretty(&summaries).expect("summaries are serializable");
+            println!("{}", json);
+        }
+    }
+
+    if !summaries.is_empty() {
+        let runs = summaries.len() as f32;
+        let mean = |value: fn(&RunSummary) -> f32| summaries.iter().map(value).sum::<f32>() / runs;
+        eprintln!(
+            "{} runs: mean score {:.0}, {:.0} m, {:.1} landings, {:.1} perfect slides, {:.1} bad landings",
+            summaries.len(),
+            mean(|summary| summary.score as f32),
+            mean(|summary| summary.distance),
+            mean(|summary| summary.landings as f32),
+            mean(|summary| summary.perfect_slides as f32),
+            mean(|summary| summary.bad_landings as f32),
+        );
+    }
+}
+
+fn parse_args(args: impl IntoIterator<Item = String>) -> Result<Options, String> {
+    let mut options = Options {
+        runs: 100,
+        first_seed: 0,
+        policy: Policy::Autopilot,
+        format: Format::Csv,
+        tuning: PathBuf::from("assets/game.tuning.ron"),
+    };
+    let mut args = args.into_iter();
+
+    while let Some(arg) = args.next() {
+        let mut value = || args.next().ok_or_else(|| format!("{} needs a value", arg));
+
+        match arg.as_str() {
+            "--runs" => {
+                let runs = value()?;
+                options.runs = runs
+                    .parse()
+                    .map_err(|_| format!("{} is not a valid number of runs", runs))?;
+            }
+            "--first-seed" => {
+                let seed = value()?;
+                options.first_seed = seed
+                    .parse()
+                    .map_err(|_| format!("{} is not a valid seed", seed))?;
+            }
+            "--policy" => {
+                options.policy = match value()?.as_str() {
+                    "glide" => Policy::Glide,
+                    "falling" => Policy::Falling,
+                    "autopilot" => Policy::Autopilot,
+                    policy => return Err(format!("unknown policy {}", policy)),
+                }
+            }
+            "--format" => {
+                options.format = match value()?.as_str() {
+                    "csv" => Format::Csv,
+                    "json" => Format::Json,
+                    format => return Err(format!("unknown format {}", format)),
+                }
+            }
+            "--tuning" => options.tuning = PathBuf::from(value()?),
+            "--help" => {
+                println!("{}", USAGE);
+                std::process::exit(0);
+            }
+            _ => return Err(format!("unknown option {}", arg)),
+        }
+    }
+
+    Ok(options)
+}
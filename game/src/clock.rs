@@ -0,0 +1,38 @@
+//! The wall clock time, which `std` can't tell on the web
+
+/// Seconds since the unix epoch
+#[cfg(not(target_arch = "wasm32"))]
+pub fn unix_time() -> f64 {
+    std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .map_or(0., |time| time.as_secs_f64())
+}
+
+#[cfg(target_arch = "wasm32")]
+pub fn unix_time() -> f64 {
+    js_sys::Date::now() / 1000.
+}
+
+/// Today's date in UTC, formatted as `YYYY-MM-DD`
+pub fn today() -> String {
+    let days = (unix_time() / 86400.).floor() as i64;
+    let (year, month, day) = civil_from_days(days);
+
+    format!("{:04}-{:02}-{:02}", year, month, day)
+}
+
+// Howard Hinnant's algorithm for converting days since the epoch to a date
+fn civil_from_days(days: i64) -> (i64, u32, u32) {
+    let z = days + 719468;
+    let era = z.div_euclid(146097);
+    let day_of_era = z.rem_euclid(146097);
+    let year_of_era =
+        (day_of_era - day_of_era / 1460 + day_of_era / 36524 - day_of_era / 146096) / 365;
+    let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+    let mp = (5 * day_of_year + 2) / 153;
+    let day = (day_of_year - (153 * mp + 2) / 5 + 1) as u32;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 } as u32;
+    let year = year_of_era + era * 400 + if month <= 2 { 1 } else { 0 };
+
+    (year, month, day)
+}
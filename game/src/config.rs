@@ -0,0 +1,122 @@
+use std::path::PathBuf;
+
+use bevy::{prelude::*, window::WindowMode};
+
+/// How the app is set up, which differs between the desktop, web and iOS builds.
+/// Which plugins are built in at all is decided by the cargo features of this crate:
+/// `dev` for the debug overlay and the tuning inspector, `diagnostics` for logging the frame rate,
+/// `hot-reload` for reloading changed assets and `audio` for sound and music
+#[derive(Debug, Clone)]
+pub struct GameConfig {
+    title: String,
+    window_size: Option<(f32, f32)>,
+    fullscreen: bool,
+    vsync: bool,
+    msaa_samples: u32,
+    pub(crate) seed: Option<u32>,
+    pub(crate) skin: Option<String>,
+    pub(crate) replay: Option<PathBuf>,
+    pub(crate) headless: bool,
+}
+
+impl Default for GameConfig {
+    fn default() -> Self {
+        Self {
+            title: "Bevy Bird".to_string(),
+            window_size: None,
+            fullscreen: false,
+            vsync: true,
+            msaa_samples: 4,
+            seed: None,
+            skin: None,
+            replay: None,
+            headless: false,
+        }
+    }
+}
+
+impl GameConfig {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn title(mut self, title: impl Into<String>) -> Self {
+        self.title = title.into();
+        self
+    }
+
+    /// Size of the window in logical pixels, Bevy picks one if not set
+    pub fn window_size(mut self, width: f32, height: f32) -> Self {
+        self.window_size = Some((width, height));
+        self
+    }
+
+    /// Cover the whole screen with a borderless window
+    pub fn fullscreen(mut self, fullscreen: bool) -> Self {
+        self.fullscreen = fullscreen;
+        self
+    }
+
+    /// Wait for the display to refresh before showing a frame
+    pub fn vsync(mut self, vsync: bool) -> Self {
+        self.vsync = vsync;
+        self
+    }
+
+    /// Samples per pixel for anti-aliasing, 1 turns it off
+    pub fn msaa_samples(mut self, samples: u32) -> Self {
+        self.msaa_samples = samples;
+        self
+    }
+
+    /// Fly over the hills from `seed`, instead of random ones
+    pub fn seed(mut self, seed: u32) -> Self {
+        self.seed = Some(seed);
+        self
+    }
+
+    /// Name of the skin to start with, instead of the one picked last time
+    pub fn skin(mut self, name: impl Into<String>) -> Self {
+        self.skin = Some(name.into());
+        self
+    }
+
+    /// Play back the replay stored in the file at `path`, instead of playing.
+    /// Its seed takes the place of the one set with [`GameConfig::seed`]
+    pub fn replay(mut self, path: impl Into<PathBuf>) -> Self {
+        self.replay = Some(path.into());
+        self
+    }
+
+    /// Run the game without a window and print how the run went, see [`crate::sim::run_headless`]
+    pub fn headless(mut self, headless: bool) -> Self {
+        self.headless = headless;
+        self
+    }
+
+    pub fn window_descriptor(&self) -> WindowDescriptor {
+        let mut window = WindowDescriptor {
+            title: self.title.clone(),
+            mode: if self.fullscreen {
+                WindowMode::BorderlessFullscreen
+            } else {
+                WindowMode::Windowed
+            },
+            vsync: self.vsync,
+            ..Default::default()
+        };
+
+        if let Some((width, height)) = self.window_size {
+            window.width = width;
+            window.height = height;
+        }
+
+        window
+    }
+
+    pub fn msaa(&self) -> Msaa {
+        Msaa {
+            samples: self.msaa_samples,
+        }
+    }
+}
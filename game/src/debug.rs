@@ -0,0 +1,222 @@
+use bevy::prelude::*;
+
+use crate::{
+    player::{PhysicsClock, PlayerComponent},
+    terrain::Terrain,
+    tuning::GameTuning,
+    GameCamera, GameSystems, HillComponent,
+};
+
+/// Draws what the physics sees on top of the game, toggled with F3:
+/// the ground curve, the ground normal below the bird, its velocity, the ceiling and the bounds of the spawned hills,
+/// with a readout of the state of the bird
+pub struct DebugOverlayPlugin;
+
+impl Plugin for DebugOverlayPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<DebugOverlay>();
+        app.add_startup_system(spawn_readout);
+        app.add_system(toggle_overlay);
+        app.add_system(clear_overlay);
+        app.add_system(draw_overlay.after(GameSystems::Camera));
+        app.add_system(readout_system.after(GameSystems::PlayerMovement));
+    }
+}
+
+#[derive(Default)]
+pub struct DebugOverlay {
+    pub enabled: bool,
+}
+
+/// A line drawn for a single frame
+#[derive(Component)]
+struct DebugLine;
+
+#[derive(Component)]
+struct DebugReadout;
+
+/// Distance between the points the ground curve is sampled at, in pixels on screen
+const GROUND_SAMPLE_SPACING: f32 = 8.;
+
+/// Width of the lines in pixels on screen
+const LINE_WIDTH: f32 = 2.;
+
+/// Length of the drawn normal, in world units
+const NORMAL_LENGTH: f32 = 60.;
+
+/// Seconds of flight the drawn velocity covers
+const VELOCITY_SCALE: f32 = 0.25;
+
+fn toggle_overlay(
+    keys: Res<Input<KeyCode>>,
+    mut overlay: ResMut<DebugOverlay>,
+    mut readouts: Query<&mut Visibility, With<DebugReadout>>,
+) {
+    if !keys.just_pressed(KeyCode::F3) {
+        return;
+    }
+
+    overlay.enabled = !overlay.enabled;
+    for mut visibility in readouts.iter_mut() {
+        visibility.is_visible = overlay.enabled;
+    }
+}
+
+fn line(from: Vec2, to: Vec2, width: f32, color: Color) -> SpriteBundle {
+    let delta = to - from;
+    let center = (from + to) / 2.;
+
+    SpriteBundle {
+        sprite: Sprite {
+            color,
+            custom_size: Some(Vec2::new(delta.length(), width)),
+            ..Default::default()
+        },
+        transform: Transform::from_xyz(center.x, center.y, 10.)
+            .with_rotation(Quat::from_rotation_z(delta.y.atan2(delta.x))),
+        ..Default::default()
+    }
+}
+
+// Lines are spawned every frame, and despawned again the next one.
+// The commands of both systems are applied at the end of the stage, so the new lines aren't despawned right away
+fn clear_overlay(mut commands: Commands, lines: Query<Entity, With<DebugLine>>) {
+    for line in lines.iter() {
+        commands.entity(line).despawn();
+    }
+}
+
+fn draw_overlay(
+    mut commands: Commands,
+    overlay: Res<DebugOverlay>,
+    cameras: Query<&Transform, With<GameCamera>>,
+    player: Query<(&Transform, &PlayerComponent)>,
+    hills: Query<&Transform, With<HillComponent>>,
+    windows: Res<Windows>,
+    (terrain, tuning): (Res<Terrain>, Res<GameTuning>),
+) {
+    if !overlay.enabled {
+        return;
+    }
+    let (Ok(camera), Some(window)) = (cameras.get_single(), windows.get_primary()) else {
+        return;
+    };
+
+    let scale = camera.scale.x;
+    let width = LINE_WIDTH * scale;
+    let half_width = window.width() / 2. * scale;
+    let half_height = window.height() / 2. * scale;
+    let left = camera.translation.x - half_width;
+    let right = camera.translation.x + half_width;
+
+    let mut draw = |from: Vec2, to: Vec2, color: Color| {
+        commands
+            .spawn_bundle(line(from, to, width, color))
+            .insert(DebugLine);
+    };
+
+    // Ground curve, as the physics samples it
+    let spacing = GROUND_SAMPLE_SPACING * scale;
+    let mut x = left;
+    while x < right {
+        let next = x + spacing;
+        draw(
+            Vec2::new(x, terrain.height(x)),
+            Vec2::new(next, terrain.height(next)),
+            Color::rgb(1., 0., 1.),
+        );
+        x = next;
+    }
+
+    draw(
+        Vec2::new(left, tuning.ceiling),
+        Vec2::new(right, tuning.ceiling),
+        Color::rgb(1., 0.5, 0.),
+    );
+
+    for hill in hills.iter() {
+        for edge in [-0.5, 0.5] {
+            let x = hill.translation.x + edge * hill.scale.x;
+            draw(
+                Vec2::new(x, camera.translation.y - half_height),
+                Vec2::new(x, camera.translation.y + half_height),
+                Color::rgba(0., 0.6, 1., 0.5),
+            );
+        }
+    }
+
+    if let Ok((transform, player)) = player.get_single() {
+        let position = transform.translation.truncate();
+        let ground = Vec2::new(position.x, terrain.height(position.x));
+
+        draw(
+            ground,
+            ground + terrain.normal(position.x) * NORMAL_LENGTH,
+            Color::rgb(0., 0.8, 0.),
+        );
+        draw(
+            position,
+            position + player.velocity * VELOCITY_SCALE,
+            Color::RED,
+        );
+    }
+}
+
+fn spawn_readout(mut commands: Commands, asset_server: Res<AssetServer>) {
+    commands
+        .spawn_bundle(TextBundle {
+            text: Text::with_section(
+                "",
+                TextStyle {
+                    font: asset_server.load("fonts/DejaVuSans.ttf"),
+                    font_size: 16.,
+                    color: Color::BLACK,
+                },
+                Default::default(),
+            ),
+            style: Style {
+                position_type: PositionType::Absolute,
+                position: Rect {
+                    top: Val::Px(12.),
+                    right: Val::Px(16.),
+                    ..Default::default()
+                },
+                ..Default::default()
+            },
+            visibility: Visibility { is_visible: false },
+            ..Default::default()
+        })
+        .insert(DebugReadout);
+}
+
+fn readout_system(
+    overlay: Res<DebugOverlay>,
+    player: Query<(&Transform, &PlayerComponent)>,
+    clock: Res<PhysicsClock>,
+    mut readouts: Query<&mut Text, With<DebugReadout>>,
+) {
+    let Ok((transform, player)) = player.get_single() else {
+        return;
+    };
+    if !overlay.enabled {
+        return;
+    }
+
+    for mut text in readouts.iter_mut() {
+        text.sections[0].value = format!(
+            "position {:.0}, {:.0}\nvelocity {:.0}, {:.0} ({:.0})\n{}{}\ntick {}",
+            transform.translation.x,
+            transform.translation.y,
+            player.velocity.x,
+            player.velocity.y,
+            player.velocity.length(),
+            if player.grounded {
+                "grounded"
+            } else {
+                "airborne"
+            },
+            if player.diving { ", diving" } else { "" },
+            clock.tick
+        );
+    }
+}
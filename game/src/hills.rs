@@ -17,11 +17,18 @@ use bevy::{
     sprite::{Material2dPipeline, Material2dPlugin, SpecializedMaterial2d},
 };
 
-pub fn hills_height(x: f32, flipped: bool) -> f32 {
-    (x * std::f32::consts::TAU).sin() * 0.1 * if flipped { -1. } else { 1. } + 1.
+use crate::terrain::smoothstep;
+
+/// Height of a chunk of hills at `x`, from 0 at its left edge to 1 at its right one.
+/// The height of the hill blends from `start_amplitude` to `end_amplitude` over the chunk,
+/// so neighbouring chunks meet without a kink
+pub fn hills_height(x: f32, start_amplitude: f32, end_amplitude: f32) -> f32 {
+    let amplitude = start_amplitude + (end_amplitude - start_amplitude) * smoothstep(x);
+
+    (x * std::f32::consts::TAU).sin() * amplitude + 1.
 }
 
-pub fn hills_mesh() -> Mesh {
+pub fn hills_mesh(start_amplitude: f32, end_amplitude: f32) -> Mesh {
     // Generate vertex positions
     const STEPS: i32 = 75;
     let mut v_pos = vec![];
@@ -29,7 +36,10 @@ pub fn hills_mesh() -> Mesh {
     for i in 0..=STEPS {
         let x_offset = (i as f32) / (STEPS as f32) - 0.5;
         v_pos.push([x_offset, 0.]);
-        v_pos.push([x_offset, hills_height((i as f32) / (STEPS as f32), false)]);
+        v_pos.push([
+            x_offset,
+            hills_height((i as f32) / (STEPS as f32), start_amplitude, end_amplitude),
+        ]);
     }
 
     // Generate indices for vertex positions
@@ -0,0 +1,564 @@
+use std::{collections::HashMap, fmt};
+
+use bevy::{input::InputSystem, prelude::*, utils::HashSet, window::WindowFocused};
+use serde::{Deserialize, Serialize};
+
+use crate::{storage, GameState};
+
+/// Maps keys, mouse buttons, touches and gamepad buttons to the actions of the game.
+/// Game systems read the [`ActionState`] rather than the raw input, so the bindings can be changed
+pub struct InputMapPlugin;
+
+impl Plugin for InputMapPlugin {
+    fn build(&self, app: &mut App) {
+        app.insert_resource(
+            storage::load::<InputMap>("input")
+                .map(InputMap::with_missing_defaults)
+                .unwrap_or_default(),
+        );
+        app.init_resource::<ActionState>();
+        app.add_system_to_stage(
+            CoreStage::PreUpdate,
+            release_input_on_blur
+                .after(InputSystem)
+                .before(ActionStateSystem),
+        );
+        app.add_system_to_stage(
+            CoreStage::PreUpdate,
+            update_action_state
+                .label(ActionStateSystem)
+                .after(InputSystem),
+        );
+        app.add_system(gamepad_connections);
+        app.add_system_set(
+            SystemSet::on_enter(GameState::Rebinding).with_system(spawn_rebinding_screen),
+        );
+        app.add_system_set(
+            SystemSet::on_update(GameState::Rebinding)
+                .with_system(rebinding_input)
+                .with_system(rebinding_text),
+        );
+        app.add_system_set(
+            SystemSet::on_exit(GameState::Rebinding).with_system(despawn_rebinding_screen),
+        );
+    }
+}
+
+/// Updates the [`ActionState`] in `CoreStage::PreUpdate`
+#[derive(Debug, Clone, PartialEq, Eq, Hash, SystemLabel)]
+pub struct ActionStateSystem;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum Action {
+    Dive,
+    Pause,
+    ToggleSkin,
+    Restart,
+    /// Starts a run over the daily islands from the title screen
+    Daily,
+    /// Starts a run flown by the autopilot from the title screen
+    Autopilot,
+    /// Opens the rebinding screen from the pause screen
+    Controls,
+    /// Opens the skin selection screen while playing
+    SkinSelect,
+    /// Browses to the previous skin on the selection screen
+    Previous,
+    /// Browses to the next skin on the selection screen
+    Next,
+    /// Picks the highlighted skin on the selection screen
+    Confirm,
+}
+
+impl Action {
+    pub const ALL: [Action; 11] = [
+        Action::Dive,
+        Action::Pause,
+        Action::ToggleSkin,
+        Action::Restart,
+        Action::Daily,
+        Action::Autopilot,
+        Action::Controls,
+        Action::SkinSelect,
+        Action::Previous,
+        Action::Next,
+        Action::Confirm,
+    ];
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Binding {
+    Key(KeyCode),
+    Mouse(MouseButton),
+    /// Any finger on the screen
+    Touch,
+    /// The button on any connected gamepad
+    Gamepad(GamepadButtonType),
+}
+
+impl fmt::Display for Binding {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Binding::Key(key) => write!(f, "{:?}", key),
+            Binding::Mouse(button) => write!(f, "Mouse {:?}", button),
+            Binding::Touch => write!(f, "Touch"),
+            Binding::Gamepad(button) => write!(f, "Gamepad {:?}", button),
+        }
+    }
+}
+
+/// The bindings of every action, saved when changed on the rebinding screen
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct InputMap {
+    bindings: HashMap<Action, Vec<Binding>>,
+}
+
+impl Default for InputMap {
+    fn default() -> Self {
+        let mut bindings = HashMap::new();
+
+        for action in Action::ALL {
+            bindings.insert(action, Self::default_bindings(action));
+        }
+
+        Self { bindings }
+    }
+}
+
+impl InputMap {
+    fn default_bindings(action: Action) -> Vec<Binding> {
+        match action {
+            Action::Dive => vec![
+                Binding::Key(KeyCode::Space),
+                Binding::Mouse(MouseButton::Left),
+                Binding::Touch,
+                Binding::Gamepad(GamepadButtonType::South),
+                Binding::Gamepad(GamepadButtonType::LeftTrigger2),
+                Binding::Gamepad(GamepadButtonType::RightTrigger2),
+            ],
+            Action::Pause => vec![
+                Binding::Key(KeyCode::Escape),
+                Binding::Key(KeyCode::P),
+                Binding::Gamepad(GamepadButtonType::Start),
+            ],
+            Action::ToggleSkin => vec![
+                Binding::Key(KeyCode::B),
+                Binding::Gamepad(GamepadButtonType::North),
+            ],
+            Action::Restart => vec![
+                Binding::Key(KeyCode::R),
+                Binding::Gamepad(GamepadButtonType::Select),
+            ],
+            Action::Daily => vec![Binding::Key(KeyCode::D)],
+            Action::Autopilot => vec![Binding::Key(KeyCode::A)],
+            Action::Controls => vec![Binding::Key(KeyCode::C)],
+            Action::SkinSelect => vec![
+                Binding::Key(KeyCode::Tab),
+                Binding::Gamepad(GamepadButtonType::West),
+            ],
+            Action::Previous => vec![
+                Binding::Key(KeyCode::Left),
+                Binding::Gamepad(GamepadButtonType::DPadLeft),
+            ],
+            Action::Next => vec![
+                Binding::Key(KeyCode::Right),
+                Binding::Gamepad(GamepadButtonType::DPadRight),
+            ],
+            Action::Confirm => vec![
+                Binding::Key(KeyCode::Return),
+                Binding::Gamepad(GamepadButtonType::East),
+            ],
+        }
+    }
+
+    /// Binds the actions missing from a map saved by an older version of the game to their defaults
+    fn with_missing_defaults(mut self) -> Self {
+        for action in Action::ALL {
+            self.bindings
+                .entry(action)
+                .or_insert_with(|| Self::default_bindings(action));
+        }
+
+        self
+    }
+
+    pub fn bindings(&self, action: Action) -> &[Binding] {
+        self.bindings.get(&action).map_or(&[], |bindings| bindings)
+    }
+
+    /// Name of the first binding of `action`, for hints on screen
+    pub fn label(&self, action: Action) -> String {
+        self.bindings(action)
+            .first()
+            .map_or_else(|| "(unbound)".to_string(), |binding| binding.to_string())
+    }
+
+    /// Bind `binding` to `action` in place of its binding at `slot`, or in addition to them if `slot` is past the last one.
+    /// An input only ever triggers one action, so if another action was bound to `binding`,
+    /// it gets the replaced binding in exchange, or loses the binding if none was replaced.
+    /// Returns that other action
+    pub fn rebind(&mut self, action: Action, slot: usize, binding: Binding) -> Option<Action> {
+        let current = self.bindings(action);
+        if let Some(existing) = current.iter().position(|&bound| bound == binding) {
+            let bindings = self.bindings.entry(action).or_default();
+            if slot < bindings.len() {
+                bindings.swap(existing, slot);
+            }
+            return None;
+        }
+        let replaced = current.get(slot).copied();
+
+        let conflict = Action::ALL
+            .into_iter()
+            .find(|&other| other != action && self.bindings(other).contains(&binding));
+        if let Some(other) = conflict {
+            let others = self.bindings.entry(other).or_default();
+            let position = others.iter().position(|&bound| bound == binding);
+
+            match (position, replaced) {
+                (Some(position), Some(replaced)) if !others.contains(&replaced) => {
+                    others[position] = replaced;
+                }
+                (Some(position), _) => {
+                    others.remove(position);
+                }
+                (None, _) => {}
+            }
+        }
+
+        let bindings = self.bindings.entry(action).or_default();
+        match bindings.get_mut(slot) {
+            Some(bound) => *bound = binding,
+            None => bindings.push(binding),
+        }
+
+        conflict
+    }
+
+    pub fn reset(&mut self, action: Action) {
+        self.bindings.insert(action, Self::default_bindings(action));
+    }
+}
+
+/// Which actions are held down this frame
+#[derive(Default)]
+pub struct ActionState {
+    pressed: HashSet<Action>,
+    just_pressed: HashSet<Action>,
+}
+
+impl ActionState {
+    pub fn pressed(&self, action: Action) -> bool {
+        self.pressed.contains(&action)
+    }
+
+    pub fn just_pressed(&self, action: Action) -> bool {
+        self.just_pressed.contains(&action)
+    }
+
+    /// Like `just_pressed`, but no other system will see the press this frame.
+    /// Used by systems changing the [`GameState`], so the systems of the new state don't react to the same press
+    pub fn consume(&mut self, action: Action) -> bool {
+        self.just_pressed.remove(&action)
+    }
+}
+
+// The window doesn't get the release of keys and buttons held while it loses focus,
+// which would leave them pressed until they are pressed and released again
+fn release_input_on_blur(
+    mut focus_events: EventReader<WindowFocused>,
+    mut keys: ResMut<Input<KeyCode>>,
+    mut mouse_buttons: ResMut<Input<MouseButton>>,
+) {
+    if !focus_events.iter().any(|event| !event.focused) {
+        return;
+    }
+
+    let pressed_keys: Vec<KeyCode> = keys.get_pressed().copied().collect();
+    for key in pressed_keys {
+        keys.reset(key);
+    }
+
+    let pressed_buttons: Vec<MouseButton> = mouse_buttons.get_pressed().copied().collect();
+    for button in pressed_buttons {
+        mouse_buttons.reset(button);
+    }
+}
+
+fn update_action_state(
+    mut actions: ResMut<ActionState>,
+    input_map: Res<InputMap>,
+    keys: Res<Input<KeyCode>>,
+    mouse_buttons: Res<Input<MouseButton>>,
+    touches: Res<Touches>,
+    gamepad_buttons: Res<Input<GamepadButton>>,
+) {
+    actions.pressed.clear();
+    actions.just_pressed.clear();
+
+    for action in Action::ALL {
+        let mut pressed = false;
+        let mut just_pressed = false;
+
+        for binding in input_map.bindings(action) {
+            match *binding {
+                Binding::Key(key) => {
+                    pressed |= keys.pressed(key);
+                    just_pressed |= keys.just_pressed(key);
+                }
+                Binding::Mouse(button) => {
+                    pressed |= mouse_buttons.pressed(button);
+                    just_pressed |= mouse_buttons.just_pressed(button);
+                }
+                Binding::Touch => {
+                    pressed |= touches.iter().next().is_some();
+                    just_pressed |= touches.iter_just_pressed().next().is_some();
+                }
+                Binding::Gamepad(button_type) => {
+                    pressed |= gamepad_buttons
+                        .get_pressed()
+                        .any(|button| button.1 == button_type);
+                    just_pressed |= gamepad_buttons
+                        .get_just_pressed()
+                        .any(|button| button.1 == button_type);
+                }
+            }
+        }
+
+        if pressed {
+            actions.pressed.insert(action);
+        }
+        if just_pressed {
+            actions.just_pressed.insert(action);
+        }
+    }
+}
+
+// Bevy releases the buttons of a disconnected gamepad, so there is nothing to clean up here.
+// Pause instead, rather than letting the bird glide on without anyone holding the controller
+fn gamepad_connections(
+    mut gamepad_events: EventReader<GamepadEvent>,
+    mut state: ResMut<State<GameState>>,
+) {
+    for GamepadEvent(gamepad, event) in gamepad_events.iter() {
+        match event {
+            GamepadEventType::Connected => info!("{:?} connected", gamepad),
+            GamepadEventType::Disconnected => {
+                info!("{:?} disconnected", gamepad);
+
+                if matches!(state.current(), GameState::Playing | GameState::Resuming) {
+                    let _ = state.set(GameState::Paused);
+                }
+            }
+            _ => {}
+        }
+    }
+}
+
+// Rebinding screen
+
+#[derive(Component)]
+struct RebindingScreen;
+
+#[derive(Component)]
+struct BindingText(usize);
+
+/// The action and binding highlighted on the rebinding screen, and whether it is waiting for a new binding.
+/// The slot past the last binding of the action adds a new binding
+#[derive(Default)]
+struct RebindingCursor {
+    index: usize,
+    slot: usize,
+    capturing: bool,
+}
+
+fn spawn_rebinding_screen(mut commands: Commands, asset_server: Res<AssetServer>) {
+    let font = asset_server.load("fonts/DejaVuSans.ttf");
+
+    commands.insert_resource(RebindingCursor::default());
+
+    let text_style = |font_size: f32| TextStyle {
+        font: font.clone(),
+        font_size,
+        color: Color::WHITE,
+    };
+
+    commands
+        .spawn_bundle(NodeBundle {
+            style: Style {
+                size: Size::new(Val::Percent(100.), Val::Percent(100.)),
+                position_type: PositionType::Absolute,
+                flex_direction: FlexDirection::ColumnReverse,
+                justify_content: JustifyContent::Center,
+                align_items: AlignItems::Center,
+                ..Default::default()
+            },
+            color: Color::rgba(0., 0., 0., 0.6).into(),
+            ..Default::default()
+        })
+        .insert(RebindingScreen)
+        .with_children(|screen| {
+            screen.spawn_bundle(TextBundle {
+                text: Text::with_section("Controls", text_style(40.), Default::default()),
+                style: Style {
+                    margin: Rect::all(Val::Px(16.)),
+                    ..Default::default()
+                },
+                ..Default::default()
+            });
+
+            for i in 0..Action::ALL.len() {
+                screen
+                    .spawn_bundle(TextBundle {
+                        text: Text::with_section("", text_style(22.), Default::default()),
+                        style: Style {
+                            margin: Rect::all(Val::Px(4.)),
+                            ..Default::default()
+                        },
+                        ..Default::default()
+                    })
+                    .insert(BindingText(i));
+            }
+
+            screen.spawn_bundle(TextBundle {
+                text: Text::with_section(
+                    "Up/Down to choose an action, Left/Right a binding, Enter to rebind, Backspace to reset, Esc to go back",
+                    text_style(16.),
+                    Default::default(),
+                ),
+                style: Style {
+                    margin: Rect::all(Val::Px(16.)),
+                    ..Default::default()
+                },
+                ..Default::default()
+            });
+        });
+}
+
+fn rebinding_input(
+    mut state: ResMut<State<GameState>>,
+    mut cursor: ResMut<RebindingCursor>,
+    mut input_map: ResMut<InputMap>,
+    mut actions: ResMut<ActionState>,
+    keys: Res<Input<KeyCode>>,
+    mouse_buttons: Res<Input<MouseButton>>,
+    gamepad_buttons: Res<Input<GamepadButton>>,
+) {
+    let action = Action::ALL[cursor.index];
+
+    if cursor.capturing {
+        // Escape cancels, so it can't be bound to anything here
+        if keys.just_pressed(KeyCode::Escape) {
+            actions.consume(Action::Pause);
+            cursor.capturing = false;
+            return;
+        }
+
+        let binding = keys
+            .get_just_pressed()
+            .next()
+            .map(|&key| Binding::Key(key))
+            .or_else(|| {
+                mouse_buttons
+                    .get_just_pressed()
+                    .next()
+                    .map(|&button| Binding::Mouse(button))
+            })
+            .or_else(|| {
+                gamepad_buttons
+                    .get_just_pressed()
+                    .next()
+                    .map(|button| Binding::Gamepad(button.1))
+            });
+
+        if let Some(binding) = binding {
+            if let Some(other) = input_map.rebind(action, cursor.slot, binding) {
+                info!("{} moved from {:?} to {:?}", binding, other, action);
+            }
+            cursor.capturing = false;
+        }
+        return;
+    }
+
+    if actions.consume(Action::Pause) || keys.just_pressed(KeyCode::Escape) {
+        let _ = state.set(GameState::Paused);
+        return;
+    }
+
+    let count = Action::ALL.len();
+    if keys.just_pressed(KeyCode::Up) {
+        cursor.index = (cursor.index + count - 1) % count;
+    }
+    if keys.just_pressed(KeyCode::Down) {
+        cursor.index = (cursor.index + 1) % count;
+    }
+    if keys.just_pressed(KeyCode::Back) {
+        input_map.reset(action);
+    }
+
+    let slots = input_map.bindings(Action::ALL[cursor.index]).len() + 1;
+    if keys.just_pressed(KeyCode::Left) {
+        cursor.slot = (cursor.slot + slots - 1) % slots;
+    }
+    if keys.just_pressed(KeyCode::Right) {
+        cursor.slot = (cursor.slot + 1) % slots;
+    }
+    // Moving to an action with fewer bindings
+    if cursor.slot >= slots {
+        cursor.slot = slots - 1;
+    }
+    if keys.just_pressed(KeyCode::Return) {
+        cursor.capturing = true;
+    }
+}
+
+fn rebinding_text(
+    cursor: Res<RebindingCursor>,
+    input_map: Res<InputMap>,
+    mut texts: Query<(&BindingText, &mut Text)>,
+) {
+    if !cursor.is_changed() && !input_map.is_changed() {
+        return;
+    }
+
+    for (binding_text, mut text) in texts.iter_mut() {
+        let action = Action::ALL[binding_text.0];
+        let selected = binding_text.0 == cursor.index;
+
+        let bindings = if selected && cursor.capturing {
+            "press a key or button...".to_string()
+        } else {
+            let mut labels: Vec<String> = input_map
+                .bindings(action)
+                .iter()
+                .map(|binding| binding.to_string())
+                .collect();
+            if selected {
+                labels.push("+".to_string());
+                labels[cursor.slot] = format!("[{}]", labels[cursor.slot]);
+            }
+            labels.join(", ")
+        };
+
+        let section = &mut text.sections[0];
+        section.value = format!("{:?}: {}", action, bindings);
+        section.style.color = if selected {
+            Color::rgb(1., 0.85, 0.3)
+        } else {
+            Color::WHITE
+        };
+    }
+}
+
+fn despawn_rebinding_screen(
+    mut commands: Commands,
+    screens: Query<Entity, With<RebindingScreen>>,
+    input_map: Res<InputMap>,
+) {
+    for screen in screens.iter() {
+        commands.entity(screen).despawn_recursive();
+    }
+    commands.remove_resource::<RebindingCursor>();
+
+    storage::save("input", &*input_map);
+}
@@ -0,0 +1,308 @@
+use bevy::prelude::*;
+
+use crate::{
+    player::PlayerComponent, trail::TrailMaterial, tuning::GameTuning, GameCamera, GameState,
+};
+
+/// A panel listing the live values of the bird, the camera, the tuning and the trail material, toggled with F4.
+/// Up and down select a value, left and right change it, holding shift changes it in bigger steps.
+/// Changed tuning values last until `assets/game.tuning.ron` is reloaded
+pub struct InspectorPlugin;
+
+impl Plugin for InspectorPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<Inspector>();
+        app.add_startup_system(spawn_panel);
+        app.add_system(inspector_input);
+        app.add_system(panel_system);
+    }
+}
+
+#[derive(Default)]
+struct Inspector {
+    open: bool,
+    selected: usize,
+}
+
+#[derive(Component)]
+struct InspectorPanel;
+
+#[derive(Debug, Clone, Copy)]
+enum Field {
+    VelocityX,
+    VelocityY,
+    Diving,
+    Grounded,
+    CameraScale,
+    Gravity,
+    DiveGravity,
+    MinGroundSpeed,
+    Ceiling,
+    CeilingDrag,
+    InitialVelocityX,
+    InitialVelocityY,
+    ZoomSpeedOffset,
+    ZoomSpeed,
+    ZoomBaseScale,
+    ZoomMaxScale,
+    HillScale,
+    TrailFade,
+    TrailAlpha,
+}
+
+impl Field {
+    const ALL: [Field; 19] = [
+        Field::VelocityX,
+        Field::VelocityY,
+        Field::Diving,
+        Field::Grounded,
+        Field::CameraScale,
+        Field::Gravity,
+        Field::DiveGravity,
+        Field::MinGroundSpeed,
+        Field::Ceiling,
+        Field::CeilingDrag,
+        Field::InitialVelocityX,
+        Field::InitialVelocityY,
+        Field::ZoomSpeedOffset,
+        Field::ZoomSpeed,
+        Field::ZoomBaseScale,
+        Field::ZoomMaxScale,
+        Field::HillScale,
+        Field::TrailFade,
+        Field::TrailAlpha,
+    ];
+
+    fn label(self) -> &'static str {
+        match self {
+            Field::VelocityX => "player velocity x",
+            Field::VelocityY => "player velocity y",
+            Field::Diving => "player diving",
+            Field::Grounded => "player grounded",
+            Field::CameraScale => "camera scale",
+            Field::Gravity => "gravity",
+            Field::DiveGravity => "dive gravity",
+            Field::MinGroundSpeed => "min ground speed",
+            Field::Ceiling => "ceiling",
+            Field::CeilingDrag => "ceiling drag",
+            Field::InitialVelocityX => "initial velocity x",
+            Field::InitialVelocityY => "initial velocity y",
+            Field::ZoomSpeedOffset => "camera speed offset",
+            Field::ZoomSpeed => "camera zoom speed",
+            Field::ZoomBaseScale => "camera base scale",
+            Field::ZoomMaxScale => "camera max scale",
+            Field::HillScale => "hill scale (game.tuning.ron)",
+            Field::TrailFade => "trail material fade",
+            Field::TrailAlpha => "trail material alpha",
+        }
+    }
+
+    /// How much one press changes the value, `None` for values which can't be changed here.
+    /// The camera and the contact of the bird are recomputed every frame,
+    /// and changing the hill scale needs the hills to be rebuilt
+    fn step(self) -> Option<f32> {
+        match self {
+            Field::Diving | Field::Grounded | Field::CameraScale | Field::HillScale => None,
+            Field::ZoomBaseScale | Field::ZoomMaxScale | Field::TrailFade | Field::TrailAlpha => {
+                Some(0.05)
+            }
+            _ => Some(10.),
+        }
+    }
+
+    fn value(
+        self,
+        player: &PlayerComponent,
+        camera_scale: f32,
+        tuning: &GameTuning,
+        trail: Option<&TrailMaterial>,
+    ) -> Option<f32> {
+        let flag = |set: bool| if set { 1. } else { 0. };
+
+        Some(match self {
+            Field::VelocityX => player.velocity.x,
+            Field::VelocityY => player.velocity.y,
+            Field::Diving => flag(player.diving),
+            Field::Grounded => flag(player.grounded),
+            Field::CameraScale => camera_scale,
+            Field::Gravity => tuning.gravity,
+            Field::DiveGravity => tuning.dive_gravity,
+            Field::MinGroundSpeed => tuning.min_ground_speed,
+            Field::Ceiling => tuning.ceiling,
+            Field::CeilingDrag => tuning.ceiling_drag,
+            Field::InitialVelocityX => tuning.initial_velocity.0,
+            Field::InitialVelocityY => tuning.initial_velocity.1,
+            Field::ZoomSpeedOffset => tuning.camera.speed_offset,
+            Field::ZoomSpeed => tuning.camera.zoom_speed,
+            Field::ZoomBaseScale => tuning.camera.base_scale,
+            Field::ZoomMaxScale => tuning.camera.max_scale,
+            Field::HillScale => tuning.hill_scale,
+            Field::TrailFade => trail?.fade,
+            Field::TrailAlpha => trail?.color.a(),
+        })
+    }
+
+    fn adjust(
+        self,
+        delta: f32,
+        player: &mut PlayerComponent,
+        tuning: &mut GameTuning,
+        trail: Option<&mut TrailMaterial>,
+    ) {
+        match self {
+            Field::VelocityX => player.velocity.x += delta,
+            Field::VelocityY => player.velocity.y += delta,
+            Field::Gravity => tuning.gravity += delta,
+            Field::DiveGravity => tuning.dive_gravity += delta,
+            Field::MinGroundSpeed => tuning.min_ground_speed += delta,
+            Field::Ceiling => tuning.ceiling += delta,
+            Field::CeilingDrag => tuning.ceiling_drag += delta,
+            Field::InitialVelocityX => tuning.initial_velocity.0 += delta,
+            Field::InitialVelocityY => tuning.initial_velocity.1 += delta,
+            Field::ZoomSpeedOffset => tuning.camera.speed_offset += delta,
+            // Dividing by zero would send the camera off to infinity
+            Field::ZoomSpeed => {
+                tuning.camera.zoom_speed = (tuning.camera.zoom_speed + delta).max(1.)
+            }
+            Field::ZoomBaseScale => tuning.camera.base_scale += delta,
+            Field::ZoomMaxScale => tuning.camera.max_scale += delta,
+            Field::TrailFade => {
+                if let Some(trail) = trail {
+                    trail.fade = (trail.fade + delta).max(0.);
+                }
+            }
+            Field::TrailAlpha => {
+                if let Some(trail) = trail {
+                    let alpha = (trail.color.a() + delta).clamp(0., 1.);
+                    trail.color.set_a(alpha);
+                }
+            }
+            Field::Diving | Field::Grounded | Field::CameraScale | Field::HillScale => {}
+        }
+    }
+}
+
+fn spawn_panel(mut commands: Commands) {
+    commands
+        .spawn_bundle(TextBundle {
+            style: Style {
+                position_type: PositionType::Absolute,
+                position: Rect {
+                    top: Val::Px(48.),
+                    left: Val::Px(16.),
+                    ..Default::default()
+                },
+                padding: Rect::all(Val::Px(8.)),
+                ..Default::default()
+            },
+            visibility: Visibility { is_visible: false },
+            ..Default::default()
+        })
+        .insert(InspectorPanel);
+}
+
+fn inspector_input(
+    keys: Res<Input<KeyCode>>,
+    state: Res<State<GameState>>,
+    mut inspector: ResMut<Inspector>,
+    mut player: Query<&mut PlayerComponent>,
+    mut tuning: ResMut<GameTuning>,
+    trails: Query<&Handle<TrailMaterial>>,
+    mut trail_materials: ResMut<Assets<TrailMaterial>>,
+) {
+    if keys.just_pressed(KeyCode::F4) {
+        inspector.open = !inspector.open;
+    }
+    // The skin select and rebinding screens use the arrow keys themselves
+    let arrows_taken = matches!(
+        state.current(),
+        GameState::SkinSelect | GameState::Rebinding
+    );
+    if !inspector.open || arrows_taken {
+        return;
+    }
+
+    let fields = Field::ALL.len();
+    if keys.just_pressed(KeyCode::Up) {
+        inspector.selected = (inspector.selected + fields - 1) % fields;
+    }
+    if keys.just_pressed(KeyCode::Down) {
+        inspector.selected = (inspector.selected + 1) % fields;
+    }
+
+    let field = Field::ALL[inspector.selected];
+    let (Some(step), Ok(mut player)) = (field.step(), player.get_single_mut()) else {
+        return;
+    };
+
+    let mut direction = 0.;
+    if keys.just_pressed(KeyCode::Left) {
+        direction -= 1.;
+    }
+    if keys.just_pressed(KeyCode::Right) {
+        direction += 1.;
+    }
+    if direction == 0. {
+        return;
+    }
+
+    let shift = keys.pressed(KeyCode::LShift) || keys.pressed(KeyCode::RShift);
+    let delta = direction * step * if shift { 10. } else { 1. };
+
+    let trail = trails
+        .iter()
+        .next()
+        .and_then(|handle| trail_materials.get_mut(handle));
+    field.adjust(delta, &mut player, &mut tuning, trail);
+}
+
+fn panel_system(
+    inspector: Res<Inspector>,
+    asset_server: Res<AssetServer>,
+    mut panels: Query<(&mut Text, &mut Visibility), With<InspectorPanel>>,
+    player: Query<&PlayerComponent>,
+    cameras: Query<&Transform, With<GameCamera>>,
+    tuning: Res<GameTuning>,
+    (trails, trail_materials): (Query<&Handle<TrailMaterial>>, Res<Assets<TrailMaterial>>),
+) {
+    let Ok((mut text, mut visibility)) = panels.get_single_mut() else {
+        return;
+    };
+    visibility.is_visible = inspector.open;
+
+    let (true, Ok(player)) = (inspector.open, player.get_single()) else {
+        return;
+    };
+    let camera_scale = cameras.get_single().map_or(1., |camera| camera.scale.x);
+    let trail = trails
+        .iter()
+        .next()
+        .and_then(|handle| trail_materials.get(handle));
+
+    let font = asset_server.load("fonts/DejaVuSans.ttf");
+    text.sections = Field::ALL
+        .iter()
+        .enumerate()
+        .map(|(index, field)| {
+            let value = match field.value(player, camera_scale, &tuning, trail) {
+                Some(value) => format!("{:.2}", value),
+                None => "-".to_string(),
+            };
+
+            TextSection {
+                value: format!("{}: {}\n", field.label(), value),
+                style: TextStyle {
+                    font: font.clone(),
+                    font_size: 14.,
+                    color: if index == inspector.selected {
+                        Color::rgb(0.9, 0.3, 0.)
+                    } else if field.step().is_some() {
+                        Color::BLACK
+                    } else {
+                        Color::GRAY
+                    },
+                },
+            }
+        })
+        .collect();
+}
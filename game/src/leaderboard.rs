@@ -0,0 +1,129 @@
+//! The best runs flown on each terrain, kept in the save file
+
+use std::collections::BTreeMap;
+
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    autopilot::Autopilot,
+    clock,
+    replay::{self, ReplayPlayback, ReplayRecorder},
+    run::Run,
+    save::{SaveData, SaveSystems},
+    storage,
+    terrain::Terrain,
+    GameState,
+};
+
+/// Records every finished run which makes it onto the leaderboard of its terrain, with a replay of it
+pub struct LeaderboardPlugin;
+
+impl Plugin for LeaderboardPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<LatestEntry>();
+        app.add_system_set(
+            SystemSet::on_enter(GameState::GameOver)
+                .with_system(record_entry.label(SaveSystems::Update)),
+        );
+    }
+}
+
+/// Runs kept on the leaderboard of each seed
+const ENTRIES_PER_SEED: usize = 10;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LeaderboardEntry {
+    pub score: u32,
+    /// Meters
+    pub distance: f32,
+    pub seed: u32,
+    /// Day the run was flown, as `YYYY-MM-DD`
+    pub date: String,
+    /// Name of the stored [`replay::Replay`] of the run
+    pub replay: Option<String>,
+}
+
+/// Rankings of runs, best first, keyed by terrain seed
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(transparent)]
+pub struct Leaderboard(BTreeMap<u32, Vec<LeaderboardEntry>>);
+
+impl Leaderboard {
+    pub fn ranking(&self, seed: u32) -> &[LeaderboardEntry] {
+        self.0.get(&seed).map_or(&[], Vec::as_slice)
+    }
+
+    /// Whether a run with `score` on the terrain with `seed` would make it onto the leaderboard
+    pub fn qualifies(&self, seed: u32, score: u32) -> bool {
+        match self.ranking(seed).get(ENTRIES_PER_SEED - 1) {
+            Some(last) => score > last.score,
+            None => true,
+        }
+    }
+
+    /// Ranks the entry among the runs on its terrain.
+    /// Returns its rank, starting at 0, and the entries which dropped off the leaderboard
+    pub fn insert(&mut self, entry: LeaderboardEntry) -> (Option<usize>, Vec<LeaderboardEntry>) {
+        let ranking = self.0.entry(entry.seed).or_default();
+
+        // On a tie, the earlier run stays ahead
+        let rank = ranking
+            .iter()
+            .position(|other| entry.score > other.score)
+            .unwrap_or(ranking.len());
+        ranking.insert(rank, entry);
+
+        let dropped = ranking.split_off(ranking.len().min(ENTRIES_PER_SEED));
+        let rank = if rank < ENTRIES_PER_SEED {
+            Some(rank)
+        } else {
+            None
+        };
+
+        (rank, dropped)
+    }
+}
+
+/// Where the last finished run ended up on the leaderboard of its terrain, if it made it
+#[derive(Default)]
+pub struct LatestEntry {
+    pub rank: Option<usize>,
+}
+
+fn record_entry(
+    run: Res<Run>,
+    terrain: Res<Terrain>,
+    recorder: Res<ReplayRecorder>,
+    mut save: ResMut<SaveData>,
+    mut latest: ResMut<LatestEntry>,
+    playback: Option<Res<ReplayPlayback>>,
+    autopilot: Option<Res<Autopilot>>,
+) {
+    let score = run.score();
+    // A replayed run is already on the leaderboard, if it made it, and the autopilot doesn't compete
+    if playback.is_some() || autopilot.is_some() || !save.leaderboard.qualifies(terrain.seed, score)
+    {
+        *latest = LatestEntry::default();
+        return;
+    }
+
+    let entry = LeaderboardEntry {
+        score,
+        distance: run.distance,
+        seed: terrain.seed,
+        date: clock::today(),
+        replay: Some(replay::save_replay(&recorder.replay(terrain.seed))),
+    };
+    let (rank, dropped) = save.leaderboard.insert(entry);
+
+    for entry in dropped {
+        if let Some(name) = entry.replay {
+            if let Err(err) = storage::remove(&name) {
+                warn!("failed to remove replay {}: {}", name, err);
+            }
+        }
+    }
+
+    *latest = LatestEntry { rank };
+}
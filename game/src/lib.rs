@@ -2,46 +2,232 @@
 
 use bevy::{asset::AssetServerSettings, prelude::*, sprite::MaterialMesh2dBundle};
 use hills::HillsMaterial;
+use terrain::Terrain;
+use tuning::GameTuning;
 
+pub use background::BackgroundPlugin;
+pub use config::GameConfig;
+pub use hills::HillsMaterialPlugin;
+pub use pause::{app_lifecycle_changed, AppLifecycle};
+pub use player::PlayerPlugin;
+
+mod animation;
+pub mod autopilot;
 mod background;
-mod bevy_player;
+mod clock;
+mod config;
+#[cfg(feature = "dev")]
+mod debug;
 mod hills;
-mod player;
+pub mod input;
+#[cfg(feature = "dev")]
+mod inspector;
+mod leaderboard;
+#[cfg(feature = "audio")]
+mod music;
+mod pause;
+pub mod player;
+mod replay;
+pub mod run;
+mod save;
+pub mod sim;
+mod skin;
+#[cfg(feature = "audio")]
+mod sound;
+mod squash;
+mod storage;
+pub mod terrain;
+mod title;
+mod trail;
+pub mod tuning;
+
+/// Build and run the game in a window, set up by `config`
+pub fn start_game(config: GameConfig) {
+    if config.headless {
+        sim::run_headless(&config);
+        return;
+    }
+
+    let mut app = App::new();
 
-pub fn start_game() {
-    App::new()
+    // Resources configuring the default plugins have to be inserted before them
+    app.insert_resource(config.window_descriptor())
         .insert_resource(AssetServerSettings {
-            watch_for_changes: true,
+            watch_for_changes: cfg!(feature = "hot-reload"),
             ..Default::default()
         })
+        .insert_resource(config.msaa())
+        .insert_resource(config)
         .add_plugins(DefaultPlugins)
-        .add_plugin(hills::HillsMaterialPlugin)
-        .add_plugin(player::PlayerPlugin)
-        .add_plugin(background::BackgroundPlugin)
+        .add_plugin(BevyBirdPlugin);
+
+    #[cfg(feature = "diagnostics")]
+    app.add_plugin(bevy::diagnostic::FrameTimeDiagnosticsPlugin)
+        .add_plugin(bevy::diagnostic::LogDiagnosticsPlugin::default());
+
+    app.run();
+}
+
+/// The whole game, to be added after Bevy's `DefaultPlugins`.
+/// Set up by the [`GameConfig`] resource if there is one.
+/// Apps assembled by hand can add the [`CorePlugin`] and pick from the plugins of the parts instead,
+/// like [`PlayerPlugin`], [`BackgroundPlugin`] and [`HillsMaterialPlugin`]
+pub struct BevyBirdPlugin;
+
+impl Plugin for BevyBirdPlugin {
+    fn build(&self, app: &mut App) {
+        let config = app
+            .world
+            .get_resource::<GameConfig>()
+            .cloned()
+            .unwrap_or_default();
+
+        let playback = config.replay.as_ref().and_then(|path| {
+            replay::load_replay_file(path)
+                .map_err(|err| error!("failed to read replay {}: {}", path.display(), err))
+                .ok()
+        });
+        let seed = match &playback {
+            Some(replay) => replay.seed,
+            None => config.seed.unwrap_or_else(terrain::random_seed),
+        };
+
+        // A replay starts playing right away
+        let initial_state = if playback.is_some() {
+            GameState::Playing
+        } else {
+            GameState::Title
+        };
+
+        app.add_plugin(CorePlugin {
+            initial_state,
+            seed,
+        })
+        .add_plugin(pause::PausePlugin)
+        .add_plugin(HillsMaterialPlugin)
+        .add_plugin(PlayerPlugin)
+        .add_plugin(BackgroundPlugin)
+        .add_plugin(trail::TrailPlugin)
+        .add_plugin(run::RunPlugin)
+        .add_plugin(replay::ReplayPlugin)
+        .add_plugin(leaderboard::LeaderboardPlugin)
+        .add_plugin(title::TitlePlugin)
         .add_startup_system(setup_world)
         .add_system(hills_system.after(GameSystems::Camera))
+        // Runs the frame after the restart, so `hills_system` doesn't extend the old hills in the meantime
+        .add_system_to_stage(CoreStage::PreUpdate, reset_hills)
         .add_system(
             camera_movement_system
                 .label(GameSystems::Camera)
                 .after(GameSystems::PlayerMovement),
         )
-        .insert_resource(Msaa { samples: 4 })
-        .insert_resource(ClearColor(Color::rgb(1., 1., 1.)))
-        // debug
-        .add_plugin(bevy::diagnostic::LogDiagnosticsPlugin::default())
-        // .add_plugin(bevy::diagnostic::FrameTimeDiagnosticsPlugin::default())
-        .run();
+        .insert_resource(ClearColor(Color::rgb(1., 1., 1.)));
+
+        // Replaces the skin picked last time, which `PlayerPlugin` set up
+        if let Some(name) = &config.skin {
+            app.insert_resource(skin::SelectedSkin { name: name.clone() });
+        }
+        if let Some(replay) = playback {
+            app.insert_resource(replay::ReplayPlayback(replay));
+        }
+        app.insert_resource(config);
+
+        #[cfg(feature = "audio")]
+        app.add_plugin(sound::SoundPlugin)
+            .add_plugin(music::MusicPlugin);
+
+        #[cfg(feature = "dev")]
+        app.add_plugin(debug::DebugOverlayPlugin)
+            .add_plugin(inspector::InspectorPlugin);
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum GameState {
+    Title,
+    Playing,
+    Paused,
+    /// Counting down before continuing to play after a pause
+    Resuming,
+    /// The day of the run is over
+    GameOver,
+    SkinSelect,
+    Rebinding,
+}
+
+/// What the plugins of the game rely on: the [`GameState`], the [`GameTime`], the [`Terrain`],
+/// the tuning, the input map and the save file.
+/// Added by [`BevyBirdPlugin`], apps assembled by hand add it before the plugins they pick
+pub struct CorePlugin {
+    pub initial_state: GameState,
+    /// Seed of the terrain of the first run
+    pub seed: u32,
+}
+
+impl Default for CorePlugin {
+    fn default() -> Self {
+        Self {
+            initial_state: GameState::Title,
+            seed: terrain::random_seed(),
+        }
+    }
+}
+
+impl Plugin for CorePlugin {
+    fn build(&self, app: &mut App) {
+        app.add_state(self.initial_state.clone())
+            .add_event::<RestartEvent>()
+            .init_resource::<GameTime>()
+            .insert_resource(Terrain::new(self.seed, GameTuning::default().hill_scale))
+            .add_system_to_stage(CoreStage::PreUpdate, update_game_time)
+            .add_plugin(tuning::TuningPlugin)
+            .add_plugin(input::InputMapPlugin)
+            .add_plugin(save::SavePlugin);
+    }
+}
+
+/// Start a new run from the beginning of the hills
+pub struct RestartEvent;
+
+/// Longest time step the game is simulated with
+const MAX_FRAME_DELTA: f32 = 1. / 20.;
+
+/// The time step the game is simulated with. Unlike the delta of `Time`, it stays small during long frames,
+/// like the first one after the app was suspended, so the bird doesn't jump ahead or fall through the ground
+#[derive(Default)]
+pub struct GameTime {
+    delta: f32,
+}
+
+impl GameTime {
+    pub fn delta_seconds(&self) -> f32 {
+        self.delta
+    }
+}
+
+fn update_game_time(time: Res<Time>, mut game_time: ResMut<GameTime>) {
+    game_time.delta = time.delta_seconds().min(MAX_FRAME_DELTA);
 }
 
 #[derive(Debug, Clone, PartialEq, Eq, Hash, SystemLabel)]
 pub enum GameSystems {
+    /// Deciding whether the bird dives
+    PlayerInput,
     PlayerMovement,
     Camera,
+    Trail,
 }
 
+/// The camera following the player, as opposed to the UI camera
+#[derive(Component)]
+pub struct GameCamera;
+
 fn setup_world(mut commands: Commands) {
-    // Spawn camera
-    commands.spawn_bundle(OrthographicCameraBundle::new_2d());
+    // Spawn cameras
+    commands
+        .spawn_bundle(OrthographicCameraBundle::new_2d())
+        .insert(GameCamera);
+    commands.spawn_bundle(UiCameraBundle::default());
 }
 
 #[derive(Component)]
@@ -53,8 +239,9 @@ fn hills_system(
     mut assets: (ResMut<Assets<Mesh>>, ResMut<Assets<HillsMaterial>>),
     asset_server: ResMut<AssetServer>,
     hills: Query<(&Transform, Entity), With<HillComponent>>,
-    cameras: Query<&Transform, With<Camera>>,
+    cameras: Query<&Transform, With<GameCamera>>,
     windows: Res<Windows>,
+    terrain: Res<Terrain>,
 ) {
     let camera_trans = cameras.get_single().unwrap();
     let window = windows.get_primary().unwrap();
@@ -80,30 +267,34 @@ fn hills_system(
         }
     }
 
+    let scale = terrain.chunk_width;
+
     if let Some((&last_trans, _)) = last_hill {
         // Spawn new hills to the right
         if camera_trans.translation.x > last_trans.translation.x - window.width() {
             spawn_hill(
                 Transform::default()
-                    .with_scale(Vec3::splat(256.))
-                    .with_translation(Vec3::new(last_trans.translation.x + 256., -256., 0.)),
+                    .with_scale(Vec3::splat(scale))
+                    .with_translation(Vec3::new(last_trans.translation.x + scale, -scale, 0.)),
                 &mut commands,
                 &mut assets,
                 &asset_server,
+                &terrain,
             );
         }
     } else {
         // If no hills were found, spawn initial ones
-        for i in 0..(window.width() as i32 / 256 * 2) {
-            let offset = window.width() - (window.width() % 256.);
+        for i in 0..(window.width() / scale) as i32 * 2 {
+            let offset = window.width() - (window.width() % scale);
 
             spawn_hill(
                 Transform::default()
-                    .with_scale(Vec3::splat(256.))
-                    .with_translation(Vec3::new((i as f32) * 256. - offset, -256., 0.)),
+                    .with_scale(Vec3::splat(scale))
+                    .with_translation(Vec3::new((i as f32) * scale - offset, -scale, 0.)),
                 &mut commands,
                 &mut assets,
                 &asset_server,
+                &terrain,
             );
         }
     }
@@ -116,12 +307,31 @@ fn hills_system(
     }
 }
 
+fn reset_hills(
+    mut commands: Commands,
+    mut restart_events: EventReader<RestartEvent>,
+    hills: Query<Entity, With<HillComponent>>,
+) {
+    if restart_events.iter().count() == 0 {
+        return;
+    }
+
+    // `hills_system` spawns the initial hills again once all are gone
+    for hill in hills.iter() {
+        commands.entity(hill).despawn();
+    }
+}
+
 fn spawn_hill(
     transform: Transform,
     commands: &mut Commands,
     (meshes, materials): &mut (ResMut<Assets<Mesh>>, ResMut<Assets<HillsMaterial>>),
     asset_server: &ResMut<AssetServer>,
+    terrain: &Terrain,
 ) {
+    let (start_amplitude, end_amplitude) =
+        terrain.chunk_amplitudes(terrain.chunk_at(transform.translation.x));
+
     // Make a new custom HillsMaterial to use with the mesh.
     // This material also specifies the structure of the vertices (vec2 for position and no normal or uv maps)
     let hills_material = HillsMaterial {
@@ -131,7 +341,9 @@ fn spawn_hill(
     // Add the mesh to the world
     commands
         .spawn_bundle(MaterialMesh2dBundle {
-            mesh: meshes.add(hills::hills_mesh()).into(),
+            mesh: meshes
+                .add(hills::hills_mesh(start_amplitude, end_amplitude))
+                .into(),
             material: materials.add(hills_material),
             transform,
             ..Default::default()
@@ -140,10 +352,11 @@ fn spawn_hill(
 }
 
 fn camera_movement_system(
-    mut cameras: Query<&mut Transform, With<Camera>>,
-    player: Query<(&Transform, &player::PlayerComponent), Without<Camera>>,
+    mut cameras: Query<&mut Transform, With<GameCamera>>,
+    player: Query<(&Transform, &player::PlayerComponent), Without<GameCamera>>,
     time: Res<Time>,
     windows: Res<Windows>,
+    tuning: Res<GameTuning>,
 ) {
     let window = windows.get_primary().unwrap();
 
@@ -152,7 +365,7 @@ fn camera_movement_system(
         .expect("only one player component should exist");
 
     for mut cam in cameras.iter_mut() {
-        let desired_scale = ((player.velocity.x + 80.) / 400. + 0.2).min(1.6);
+        let desired_scale = tuning.camera.scale(player.velocity);
 
         let dist = time.delta_seconds().min(1.);
         let new_scale = cam.scale.x * (1. - dist) + desired_scale * dist;
@@ -164,7 +377,7 @@ fn camera_movement_system(
         cam.translation.y = player_trans
             .translation
             .y
-            .min(256. / new_scale)
-            .max((window.height() / 2. - 256. / new_scale) * new_scale);
+            .min(tuning.hill_scale / new_scale)
+            .max((window.height() / 2. - tuning.hill_scale / new_scale) * new_scale);
     }
 }
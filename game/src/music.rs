@@ -0,0 +1,172 @@
+use bevy::{
+    asset::LoadState,
+    audio::{Audio, AudioSink, AudioSource, PlaybackSettings},
+    prelude::*,
+};
+
+use crate::{player::PlayerComponent, run::Run, sound::Mixer, GameState, GameSystems};
+
+/// Background music made of stems which loop together and fade in and out with the game:
+/// a calm base, layers joining in the faster the bird flies, a fever layer, and an outro at sunset
+pub struct MusicPlugin;
+
+impl Plugin for MusicPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_startup_system(load_music);
+        app.add_system(start_music);
+        app.add_system(music_system.after(GameSystems::PlayerMovement));
+        app.add_system_set(SystemSet::on_enter(GameState::GameOver).with_system(play_sunset));
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Stem {
+    Base,
+    /// Bass and shaker, while gliding at a good speed
+    Glide,
+    /// Arpeggios, while flying fast
+    Rush,
+    Fever,
+}
+
+impl Stem {
+    const ALL: [Stem; 4] = [Stem::Base, Stem::Glide, Stem::Rush, Stem::Fever];
+
+    fn path(self) -> &'static str {
+        match self {
+            Stem::Base => "music/base.wav",
+            Stem::Glide => "music/glide.wav",
+            Stem::Rush => "music/rush.wav",
+            Stem::Fever => "music/fever.wav",
+        }
+    }
+
+    /// How loud the stem should currently be, from 0 to 1
+    fn target_volume(self, state: &GameState, player: &PlayerComponent, run: &Run) -> f32 {
+        let playing = matches!(state, GameState::Playing | GameState::Resuming);
+        let speed = player.velocity.length();
+
+        match self {
+            Stem::Base => match state {
+                GameState::GameOver => 0.,
+                _ if playing => 1.,
+                // Quieter in the menus
+                _ => 0.5,
+            },
+            // Only the base plays while sliding along the ground
+            Stem::Glide if playing && !player.grounded => ramp(speed, 250., 500.),
+            Stem::Rush if playing && !player.grounded => ramp(speed, 500., 900.),
+            Stem::Fever if playing && run.fever() => 1.,
+            _ => 0.,
+        }
+    }
+}
+
+/// 0 below `from`, 1 above `to` and linear in between
+fn ramp(value: f32, from: f32, to: f32) -> f32 {
+    ((value - from) / (to - from)).clamp(0., 1.)
+}
+
+/// Volume change per second when fading stems in and out
+const FADE_IN_SPEED: f32 = 1.;
+const FADE_OUT_SPEED: f32 = 0.6;
+
+struct StemSink {
+    stem: Stem,
+    sink: Handle<AudioSink>,
+    volume: f32,
+}
+
+/// The stems, once they are playing
+struct MusicStems(Vec<StemSink>);
+
+/// The stems while they are loading
+struct StemSources(Vec<(Stem, Handle<AudioSource>)>);
+
+fn load_music(mut commands: Commands, asset_server: Res<AssetServer>) {
+    let sources = Stem::ALL
+        .iter()
+        .map(|&stem| (stem, asset_server.load(stem.path())))
+        .collect();
+
+    commands.insert_resource(StemSources(sources));
+}
+
+// All stems are started in the same frame and loop forever, silent stems just have their volume at 0.
+// That way they stay in sync, as they are all of the same length. A stem only starts playing once it is loaded,
+// so they have to wait for the slowest one
+fn start_music(
+    mut commands: Commands,
+    sources: Option<Res<StemSources>>,
+    asset_server: Res<AssetServer>,
+    audio: Res<Audio>,
+    sinks: Res<Assets<AudioSink>>,
+) {
+    let Some(sources) = sources else {
+        return;
+    };
+
+    match asset_server.get_group_load_state(sources.0.iter().map(|(_, source)| source.id)) {
+        LoadState::Loaded => {}
+        LoadState::Failed => {
+            warn!("failed to load the music, playing without it");
+            commands.remove_resource::<StemSources>();
+            return;
+        }
+        _ => return,
+    }
+
+    let stems = sources
+        .0
+        .iter()
+        .map(|(stem, source)| {
+            let sink =
+                audio.play_with_settings(source.clone(), PlaybackSettings::LOOP.with_volume(0.));
+
+            StemSink {
+                stem: *stem,
+                sink: sinks.get_handle(sink),
+                volume: 0.,
+            }
+        })
+        .collect();
+
+    commands.insert_resource(MusicStems(stems));
+    commands.remove_resource::<StemSources>();
+}
+
+fn music_system(
+    stems: Option<ResMut<MusicStems>>,
+    sinks: Res<Assets<AudioSink>>,
+    mixer: Res<Mixer>,
+    state: Res<State<GameState>>,
+    run: Res<Run>,
+    player: Query<&PlayerComponent>,
+    time: Res<Time>,
+) {
+    let (Some(mut stems), Ok(player)) = (stems, player.get_single()) else {
+        return;
+    };
+    let dt = time.delta_seconds();
+
+    for stem in stems.0.iter_mut() {
+        let target = stem.stem.target_volume(state.current(), player, &run);
+
+        stem.volume = if stem.volume < target {
+            (stem.volume + FADE_IN_SPEED * dt).min(target)
+        } else {
+            (stem.volume - FADE_OUT_SPEED * dt).max(target)
+        };
+
+        if let Some(sink) = sinks.get(&stem.sink) {
+            sink.set_volume(stem.volume * mixer.music_volume());
+        }
+    }
+}
+
+fn play_sunset(asset_server: Res<AssetServer>, audio: Res<Audio>, mixer: Res<Mixer>) {
+    audio.play_with_settings(
+        asset_server.load("music/sunset.wav"),
+        PlaybackSettings::ONCE.with_volume(mixer.music_volume()),
+    );
+}
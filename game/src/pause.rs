@@ -0,0 +1,268 @@
+use std::sync::atomic::{AtomicU32, Ordering};
+
+use bevy::{prelude::*, window::WindowFocused};
+
+use crate::{
+    input::{Action, ActionState},
+    GameState, GameTime, RestartEvent,
+};
+
+/// Pausing and restarting the game. The pause screen also leads to the rebinding screen.
+/// The game pauses by itself when the window loses focus or the app was suspended,
+/// and counts down before the bird starts moving again
+pub struct PausePlugin;
+
+impl Plugin for PausePlugin {
+    fn build(&self, app: &mut App) {
+        app.add_event::<AppLifecycle>();
+        app.init_resource::<FocusPause>();
+        app.add_system_to_stage(CoreStage::PreUpdate, send_lifecycle_events);
+        app.add_system(pause_on_focus_change);
+        app.add_system_set(SystemSet::on_update(GameState::Playing).with_system(playing_input));
+        app.add_system_set(SystemSet::on_enter(GameState::Paused).with_system(spawn_pause_screen));
+        app.add_system_set(SystemSet::on_update(GameState::Paused).with_system(paused_input));
+        app.add_system_set(SystemSet::on_exit(GameState::Paused).with_system(despawn_pause_screen));
+        app.add_system_set(
+            SystemSet::on_enter(GameState::Resuming).with_system(spawn_resume_countdown),
+        );
+        app.add_system_set(
+            SystemSet::on_update(GameState::Resuming).with_system(resume_countdown_system),
+        );
+        app.add_system_set(
+            SystemSet::on_exit(GameState::Resuming).with_system(despawn_resume_countdown),
+        );
+    }
+}
+
+/// Seconds counted down before the game continues after a pause
+const RESUME_COUNTDOWN: f32 = 3.;
+
+/// Sent when the app is moved to the background and back.
+/// Bevy doesn't notice this by itself, the platform reports it through [`app_lifecycle_changed`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AppLifecycle {
+    Suspended,
+    Resumed,
+}
+
+/// Suspends and resumes reported since the last frame, turned into events in the next one.
+/// iOS stops updating the app while it is in the background, so both usually arrive between two frames
+static PENDING_SUSPENDS: AtomicU32 = AtomicU32::new(0);
+static PENDING_RESUMES: AtomicU32 = AtomicU32::new(0);
+
+/// Tells the game the app was suspended or resumed. Called by the iOS app, from outside of Bevy's schedule
+pub fn app_lifecycle_changed(change: AppLifecycle) {
+    let pending = match change {
+        AppLifecycle::Suspended => &PENDING_SUSPENDS,
+        AppLifecycle::Resumed => &PENDING_RESUMES,
+    };
+    pending.fetch_add(1, Ordering::Relaxed);
+}
+
+/// Whether the game was paused because the window lost focus, so it can resume once focused again
+#[derive(Default)]
+struct FocusPause(bool);
+
+#[derive(Component)]
+struct PauseScreen;
+
+#[derive(Component)]
+struct CountdownText;
+
+struct ResumeCountdown(Timer);
+
+fn playing_input(
+    mut actions: ResMut<ActionState>,
+    mut state: ResMut<State<GameState>>,
+    mut restart_events: EventWriter<RestartEvent>,
+) {
+    if actions.consume(Action::Pause) {
+        let _ = state.set(GameState::Paused);
+    }
+
+    if actions.consume(Action::Restart) {
+        restart_events.send(RestartEvent);
+    }
+}
+
+fn paused_input(
+    mut actions: ResMut<ActionState>,
+    mut state: ResMut<State<GameState>>,
+    mut restart_events: EventWriter<RestartEvent>,
+    mut focus_pause: ResMut<FocusPause>,
+) {
+    if actions.consume(Action::Pause) {
+        let _ = state.set(GameState::Resuming);
+    } else if actions.consume(Action::Restart) {
+        restart_events.send(RestartEvent);
+        let _ = state.set(GameState::Resuming);
+    } else if actions.consume(Action::Controls) {
+        let _ = state.set(GameState::Rebinding);
+    } else {
+        return;
+    }
+
+    focus_pause.0 = false;
+}
+
+// A suspend is sent before a resume reported in the same frame, as the app can only be resumed after it
+fn send_lifecycle_events(mut lifecycle_events: EventWriter<AppLifecycle>) {
+    for _ in 0..PENDING_SUSPENDS.swap(0, Ordering::Relaxed) {
+        lifecycle_events.send(AppLifecycle::Suspended);
+    }
+    for _ in 0..PENDING_RESUMES.swap(0, Ordering::Relaxed) {
+        lifecycle_events.send(AppLifecycle::Resumed);
+    }
+}
+
+// Coming back from the background, the game stays paused until the player resumes it.
+// The first frame after resuming is simulated with a clamped time step, see `GameTime`
+fn pause_on_focus_change(
+    mut focus_events: EventReader<WindowFocused>,
+    mut lifecycle_events: EventReader<AppLifecycle>,
+    mut state: ResMut<State<GameState>>,
+    mut focus_pause: ResMut<FocusPause>,
+) {
+    let suspended = lifecycle_events
+        .iter()
+        .any(|change| *change == AppLifecycle::Suspended);
+    if suspended {
+        if matches!(state.current(), GameState::Playing | GameState::Resuming) {
+            let _ = state.set(GameState::Paused);
+        }
+        focus_pause.0 = false;
+        return;
+    }
+
+    let Some(focused) = focus_events.iter().map(|event| event.focused).next_back() else {
+        return;
+    };
+
+    match state.current() {
+        GameState::Playing | GameState::Resuming if !focused => {
+            focus_pause.0 = true;
+            let _ = state.set(GameState::Paused);
+        }
+        GameState::Paused if focused && focus_pause.0 => {
+            focus_pause.0 = false;
+            let _ = state.set(GameState::Resuming);
+        }
+        _ => {}
+    }
+}
+
+fn spawn_pause_screen(mut commands: Commands, asset_server: Res<AssetServer>) {
+    let font = asset_server.load("fonts/DejaVuSans.ttf");
+
+    let text_style = |font_size: f32| TextStyle {
+        font: font.clone(),
+        font_size,
+        color: Color::WHITE,
+    };
+
+    commands
+        .spawn_bundle(overlay_node(0.4))
+        .insert(PauseScreen)
+        .with_children(|screen| {
+            screen.spawn_bundle(TextBundle {
+                text: Text::with_section("Paused", text_style(40.), Default::default()),
+                style: Style {
+                    margin: Rect::all(Val::Px(16.)),
+                    ..Default::default()
+                },
+                ..Default::default()
+            });
+
+            screen.spawn_bundle(TextBundle {
+                text: Text::with_section(
+                    "Esc to resume, R to restart, C for controls",
+                    text_style(16.),
+                    Default::default(),
+                ),
+                ..Default::default()
+            });
+        });
+}
+
+fn despawn_pause_screen(mut commands: Commands, screens: Query<Entity, With<PauseScreen>>) {
+    for screen in screens.iter() {
+        commands.entity(screen).despawn_recursive();
+    }
+}
+
+fn spawn_resume_countdown(mut commands: Commands, asset_server: Res<AssetServer>) {
+    commands.insert_resource(ResumeCountdown(Timer::from_seconds(
+        RESUME_COUNTDOWN,
+        false,
+    )));
+
+    commands
+        .spawn_bundle(overlay_node(0.))
+        .insert(PauseScreen)
+        .with_children(|screen| {
+            screen
+                .spawn_bundle(TextBundle {
+                    text: Text::with_section(
+                        "",
+                        TextStyle {
+                            font: asset_server.load("fonts/DejaVuSans.ttf"),
+                            font_size: 80.,
+                            color: Color::WHITE,
+                        },
+                        Default::default(),
+                    ),
+                    ..Default::default()
+                })
+                .insert(CountdownText);
+        });
+}
+
+fn resume_countdown_system(
+    mut countdown: ResMut<ResumeCountdown>,
+    mut texts: Query<&mut Text, With<CountdownText>>,
+    mut state: ResMut<State<GameState>>,
+    mut actions: ResMut<ActionState>,
+    game_time: Res<GameTime>,
+) {
+    if actions.consume(Action::Pause) {
+        let _ = state.set(GameState::Paused);
+        return;
+    }
+
+    countdown.0.tick(std::time::Duration::from_secs_f32(
+        game_time.delta_seconds(),
+    ));
+
+    if countdown.0.finished() {
+        let _ = state.set(GameState::Playing);
+        return;
+    }
+
+    let remaining = countdown.0.duration().as_secs_f32() - countdown.0.elapsed_secs();
+    for mut text in texts.iter_mut() {
+        text.sections[0].value = format!("{}", remaining.ceil());
+    }
+}
+
+fn despawn_resume_countdown(mut commands: Commands, screens: Query<Entity, With<PauseScreen>>) {
+    for screen in screens.iter() {
+        commands.entity(screen).despawn_recursive();
+    }
+    commands.remove_resource::<ResumeCountdown>();
+}
+
+/// Full screen node darkening the game by `darkness`, laying out its children in a centered column
+pub fn overlay_node(darkness: f32) -> NodeBundle {
+    NodeBundle {
+        style: Style {
+            size: Size::new(Val::Percent(100.), Val::Percent(100.)),
+            position_type: PositionType::Absolute,
+            flex_direction: FlexDirection::ColumnReverse,
+            justify_content: JustifyContent::Center,
+            align_items: AlignItems::Center,
+            ..Default::default()
+        },
+        color: Color::rgba(0., 0., 0., darkness).into(),
+        ..Default::default()
+    }
+}
@@ -1,15 +1,47 @@
 use bevy::prelude::*;
 
-use crate::{bevy_player::BevyPlayerPlugin, hills::hills_height, GameSystems};
+use crate::{
+    animation::AnimationPlugin,
+    autopilot::Autopilot,
+    input::{Action, ActionState},
+    replay::ReplayPlayback,
+    run::DAY_TICKS,
+    skin::SkinPlugin,
+    squash::SquashPlugin,
+    terrain::Terrain,
+    tuning::GameTuning,
+    GameState, GameSystems, GameTime, RestartEvent,
+};
 
+/// The bird: its physics, input, skin and animation.
+/// Relies on the terrain, tuning, input and save resources set up by [`crate::CorePlugin`]
 pub struct PlayerPlugin;
 
 impl Plugin for PlayerPlugin {
     fn build(&self, app: &mut bevy::prelude::App) {
+        app.add_event::<GroundContactEvent>();
+        app.init_resource::<PhysicsClock>();
         app.add_startup_system(make_player);
-        app.add_system(player_input.before(GameSystems::PlayerMovement));
-        app.add_system(player_system.label(GameSystems::PlayerMovement));
-        app.add_plugin(BevyPlayerPlugin);
+        app.add_system(
+            player_input
+                .with_run_criteria(State::on_update(GameState::Playing))
+                .label(GameSystems::PlayerInput)
+                .before(GameSystems::PlayerMovement),
+        );
+        app.add_system(
+            advance_physics_clock
+                .with_run_criteria(State::on_update(GameState::Playing))
+                .before(GameSystems::PlayerMovement),
+        );
+        app.add_system(
+            player_system
+                .with_run_criteria(State::on_update(GameState::Playing))
+                .label(GameSystems::PlayerMovement),
+        );
+        app.add_system(reset_player.before(GameSystems::PlayerMovement));
+        app.add_plugin(SkinPlugin);
+        app.add_plugin(AnimationPlugin);
+        app.add_plugin(SquashPlugin);
     }
 }
 
@@ -17,58 +49,177 @@ impl Plugin for PlayerPlugin {
 pub struct PlayerComponent {
     pub velocity: Vec2,
     pub diving: bool,
-    pub bevy_mode: bool,
+    /// Whether the player is touching the ground
+    pub grounded: bool,
+    /// Seconds since the player left the ground, 0 while touching it
+    pub airtime: f32,
 }
 
-impl Default for PlayerComponent {
-    fn default() -> Self {
+impl PlayerComponent {
+    /// The player at the start of a run
+    pub fn new(tuning: &GameTuning) -> Self {
         Self {
-            velocity: Vec2::new(200., 0.),
+            velocity: tuning.initial_velocity(),
             diving: false,
-            bevy_mode: false,
+            grounded: false,
+            airtime: 0.,
         }
     }
 }
 
-fn make_player(mut commands: Commands, asset_server: Res<AssetServer>) {
+/// Sent by `player_system` when the player touches down on or takes off from the ground
+pub enum GroundContactEvent {
+    Landed(Touchdown),
+    Launched { velocity: Vec2 },
+}
+
+/// How the player touched down on the ground
+#[derive(Debug, Clone, Copy)]
+pub struct Touchdown {
+    /// Speed the player hit the ground with, along the ground normal
+    pub impact: f32,
+    /// Seconds the player was in the air before landing
+    pub airtime: f32,
+    pub ground_normal: Vec2,
+    pub diving: bool,
+}
+
+/// Height of the center of the bird above the ground when it touches it
+pub const GROUND_CLEARANCE: f32 = 16.;
+
+pub fn start_transform() -> Transform {
+    Transform::from_xyz(0., 100., 1.)
+}
+
+// The sprite of the bird is added as a child by the skin plugin
+fn make_player(mut commands: Commands, tuning: Res<GameTuning>) {
     commands
-        .spawn_bundle(SpriteBundle {
-            texture: asset_server.load("textures/player.png"),
-            sprite: Sprite {
-                custom_size: Some(Vec2::new(50.0, 50.0)),
-                ..Default::default()
-            },
-            transform: Transform::from_xyz(0., 100., 1.),
-            ..Default::default()
-        })
-        .insert(PlayerComponent::default());
+        .spawn_bundle((
+            start_transform(),
+            GlobalTransform::default(),
+            Visibility::default(),
+        ))
+        .insert(PlayerComponent::new(&tuning))
+        .insert(PhysicsPose::new(start_transform()));
 }
 
-fn ground_y(x: f32) -> f32 {
-    return hills_height(x / 256., true) * 256. - 256.;
+/// Where the physics put the player on the last two steps.
+/// Gameplay reads `current`, while the `Transform` of the player is drawn between the two,
+/// so the bird moves smoothly at frame rates that aren't a multiple of the physics rate
+#[derive(Component, Clone, Copy)]
+pub struct PhysicsPose {
+    pub previous: Transform,
+    pub current: Transform,
 }
 
-fn ground_normal(x: f32) -> Vec2 {
-    let derivative = (x / 256. * std::f32::consts::TAU).cos() * -1.;
+impl PhysicsPose {
+    pub fn new(transform: Transform) -> Self {
+        Self {
+            previous: transform,
+            current: transform,
+        }
+    }
+
+    /// The pose `alpha` of the way from `previous` to `current`
+    pub fn interpolate(&self, alpha: f32) -> Transform {
+        Transform {
+            translation: self
+                .previous
+                .translation
+                .lerp(self.current.translation, alpha),
+            rotation: self.previous.rotation.slerp(self.current.rotation, alpha),
+            scale: self.current.scale,
+        }
+    }
+}
 
-    let normal = Vec2::new(-derivative, 1.).normalize();
-    return normal;
+/// Length of a physics step. The player is moved in steps of a fixed length,
+/// so a run plays out exactly the same for the same terrain and input, whatever the frame rate
+pub const PHYSICS_STEP: f32 = 1. / PHYSICS_RATE as f32;
+
+/// Physics steps per second
+pub const PHYSICS_RATE: u64 = 120;
+
+/// Counts the physics steps taken in the current run
+#[derive(Default)]
+pub struct PhysicsClock {
+    /// Time left over from the last frame, shorter than a step
+    accumulator: f32,
+    pub tick: u64,
 }
 
-fn player_system(mut player: Query<(&mut Transform, &mut PlayerComponent)>, time: Res<Time>) {
-    let (mut transform, mut player) = player
+fn advance_physics_clock(mut clock: ResMut<PhysicsClock>, time: Res<GameTime>) {
+    clock.accumulator += time.delta_seconds();
+}
+
+fn player_system(
+    mut player: Query<(&mut Transform, &mut PhysicsPose, &mut PlayerComponent)>,
+    mut contact_events: EventWriter<GroundContactEvent>,
+    mut clock: ResMut<PhysicsClock>,
+    terrain: Res<Terrain>,
+    tuning: Res<GameTuning>,
+    playback: Option<Res<ReplayPlayback>>,
+    autopilot: Option<Res<Autopilot>>,
+) {
+    let (mut transform, mut pose, mut player) = player
         .get_single_mut()
         .expect("only one player component should exist");
 
-    player.velocity.y -= if player.diving { 400. } else { 100. } * time.delta_seconds();
+    while clock.accumulator >= PHYSICS_STEP && clock.tick < DAY_TICKS {
+        clock.accumulator -= PHYSICS_STEP;
+        clock.tick += 1;
 
-    let mut new_transform = transform.clone();
+        // A replay or the autopilot can change the input between the steps of a frame
+        if let Some(playback) = &playback {
+            player.diving = playback.0.diving_at(clock.tick);
+        } else if let Some(autopilot) = &autopilot {
+            player.diving = autopilot.dive(&pose.current, &player, &terrain, &tuning);
+        }
+
+        pose.previous = pose.current;
+        if let Some(event) = step_player(
+            &mut pose.current,
+            &mut player,
+            &terrain,
+            &tuning,
+            PHYSICS_STEP,
+        ) {
+            contact_events.send(event);
+        }
+    }
+
+    // The time left over is how far the frame is into the next step.
+    // Once the day is over no more steps are taken, so it only grows
+    let alpha = (clock.accumulator / PHYSICS_STEP).min(1.);
+    *transform = pose.interpolate(alpha);
+}
+
+/// Move the player ahead by `dt` seconds.
+/// Returns an event if the player touched down on or took off from the ground
+pub fn step_player(
+    transform: &mut Transform,
+    player: &mut PlayerComponent,
+    terrain: &Terrain,
+    tuning: &GameTuning,
+    dt: f32,
+) -> Option<GroundContactEvent> {
+    player.velocity.y -= if player.diving {
+        tuning.dive_gravity
+    } else {
+        tuning.gravity
+    } * dt;
+
+    let mut new_transform = *transform;
 
-    new_transform.translation.y += player.velocity.y * time.delta_seconds();
-    new_transform.translation.x += player.velocity.x * time.delta_seconds();
+    new_transform.translation.y += player.velocity.y * dt;
+    new_transform.translation.x += player.velocity.x * dt;
 
-    let ground_normal = ground_normal(new_transform.translation.x);
-    let ground_y = ground_y(new_transform.translation.x) + 16.;
+    let ground_normal = terrain.normal(new_transform.translation.x);
+    let ground_y = terrain.height(new_transform.translation.x) + GROUND_CLEARANCE;
+
+    let was_grounded = player.grounded;
+    // Taken before the velocity follows the ground, also when touching down within the slack below
+    let impact = (-player.velocity.dot(ground_normal)).max(0.);
 
     if new_transform.translation.y < ground_y {
         new_transform.translation.y = ground_y;
@@ -77,13 +228,16 @@ fn player_system(mut player: Query<(&mut Transform, &mut PlayerComponent)>, time
         let mut new_velocity =
             fwd * fwd.dot(player.velocity.normalize()) * player.velocity.length();
 
-        new_velocity.x = new_velocity.x.max(80.);
+        new_velocity.x = new_velocity.x.max(tuning.min_ground_speed);
 
         player.velocity = new_velocity;
     }
 
-    if new_transform.translation.y > 360. && player.velocity.y > 0. {
-        player.velocity.y -= 300. * time.delta_seconds();
+    // Allow a little slack, so the contact doesn't flicker while sliding down a slope
+    player.grounded = new_transform.translation.y <= ground_y + 1.;
+
+    if new_transform.translation.y > tuning.ceiling && player.velocity.y > 0. {
+        player.velocity.y -= tuning.ceiling_drag * dt;
     }
 
     new_transform.rotation = Quat::from_euler(
@@ -95,20 +249,49 @@ fn player_system(mut player: Query<(&mut Transform, &mut PlayerComponent)>, time
 
     transform.translation = new_transform.translation;
     transform.rotation = new_transform.rotation;
+
+    let airtime = player.airtime + dt;
+    player.airtime = if player.grounded { 0. } else { airtime };
+
+    if player.grounded && !was_grounded {
+        Some(GroundContactEvent::Landed(Touchdown {
+            impact,
+            airtime,
+            ground_normal,
+            diving: player.diving,
+        }))
+    } else if !player.grounded && was_grounded {
+        Some(GroundContactEvent::Launched {
+            velocity: player.velocity,
+        })
+    } else {
+        None
+    }
 }
 
-fn player_input(
-    mut player: Query<&mut PlayerComponent>,
-    keys: Res<Input<KeyCode>>,
-    touches: Res<Touches>,
-) {
+fn player_input(mut player: Query<&mut PlayerComponent>, actions: Res<ActionState>) {
     let mut player = player
         .get_single_mut()
         .expect("only one player component should exist");
 
-    player.diving = keys.pressed(KeyCode::Space) || touches.iter().next().is_some();
+    player.diving = actions.pressed(Action::Dive);
+}
+
+fn reset_player(
+    mut restart_events: EventReader<RestartEvent>,
+    mut player: Query<(&mut Transform, &mut PhysicsPose, &mut PlayerComponent)>,
+    mut clock: ResMut<PhysicsClock>,
+    tuning: Res<GameTuning>,
+) {
+    if restart_events.iter().count() == 0 {
+        return;
+    }
+
+    *clock = PhysicsClock::default();
 
-    if keys.just_pressed(KeyCode::B) {
-        player.bevy_mode = !player.bevy_mode;
+    for (mut transform, mut pose, mut player) in player.iter_mut() {
+        *transform = start_transform();
+        *pose = PhysicsPose::new(start_transform());
+        *player = PlayerComponent::new(&tuning);
     }
 }
@@ -0,0 +1,112 @@
+//! Recordings of runs. As the physics runs in fixed steps on seeded terrain,
+//! the moments the bird started and stopped diving are all it takes to play a run back
+
+use std::path::Path;
+
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    player::{PhysicsClock, PlayerComponent},
+    storage, GameState, GameSystems, RestartEvent,
+};
+
+/// Records the input of the current run
+pub struct ReplayPlugin;
+
+impl Plugin for ReplayPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<ReplayRecorder>();
+        app.add_system(reset_recorder);
+        app.add_system_set(
+            SystemSet::on_update(GameState::Playing).with_system(
+                record_dive_toggles
+                    .after(GameSystems::PlayerInput)
+                    .before(GameSystems::PlayerMovement),
+            ),
+        );
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Replay {
+    /// Seed of the terrain the run was flown on
+    pub seed: u32,
+    /// Physics ticks after which the bird started or stopped diving, starting out not diving
+    pub dive_toggles: Vec<u64>,
+}
+
+impl Replay {
+    /// Whether the bird dives during the physics step taking the clock to `tick`
+    pub fn diving_at(&self, tick: u64) -> bool {
+        let toggles = self
+            .dive_toggles
+            .iter()
+            .take_while(|&&toggle| toggle < tick)
+            .count();
+
+        toggles % 2 == 1
+    }
+}
+
+/// Reads a replay from a file, like one of the replays stored with the leaderboard
+pub fn load_replay_file(path: &Path) -> anyhow::Result<Replay> {
+    let contents = std::fs::read_to_string(path)?;
+
+    Ok(ron::from_str(&contents)?)
+}
+
+/// While this resource exists, the bird follows the replay instead of the input
+pub struct ReplayPlayback(pub Replay);
+
+/// Stores the replay, returning the name it can be loaded with
+pub fn save_replay(replay: &Replay) -> String {
+    let name = format!(
+        "replays/{}-{}",
+        replay.seed,
+        (crate::clock::unix_time() * 1000.) as u64
+    );
+    storage::save(&name, replay);
+
+    name
+}
+
+#[derive(Default)]
+pub struct ReplayRecorder {
+    diving: bool,
+    dive_toggles: Vec<u64>,
+}
+
+impl ReplayRecorder {
+    /// The run recorded so far
+    pub fn replay(&self, seed: u32) -> Replay {
+        Replay {
+            seed,
+            dive_toggles: self.dive_toggles.clone(),
+        }
+    }
+}
+
+fn record_dive_toggles(
+    mut recorder: ResMut<ReplayRecorder>,
+    player: Query<&PlayerComponent>,
+    clock: Res<PhysicsClock>,
+) {
+    let Ok(player) = player.get_single() else {
+        return;
+    };
+
+    if player.diving != recorder.diving {
+        recorder.diving = player.diving;
+        recorder.dive_toggles.push(clock.tick);
+    }
+}
+
+fn reset_recorder(
+    mut restart_events: EventReader<RestartEvent>,
+    mut recorder: ResMut<ReplayRecorder>,
+) {
+    if restart_events.iter().count() > 0 {
+        *recorder = ReplayRecorder::default();
+    }
+}
@@ -0,0 +1,378 @@
+use bevy::prelude::*;
+
+use crate::{
+    input::{Action, ActionState},
+    leaderboard::LatestEntry,
+    player::{
+        GroundContactEvent, PhysicsClock, PhysicsPose, PlayerComponent, Touchdown, PHYSICS_RATE,
+        PHYSICS_STEP,
+    },
+    save::{SaveData, SaveSystems},
+    terrain::Terrain,
+    title::GameMode,
+    GameState, GameSystems, RestartEvent,
+};
+
+/// A run lasts one day. The score is the distance flown, plus a bonus for every perfect slide.
+/// When the sun sets the run is over
+pub struct RunPlugin;
+
+impl Plugin for RunPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<Run>();
+        app.add_event::<LandingEvent>();
+        app.add_startup_system(spawn_hud);
+        app.add_system(reset_run);
+        app.add_system(landing_system.after(GameSystems::PlayerMovement));
+        app.add_system(hud_system);
+        app.add_system_set(
+            SystemSet::on_update(GameState::Playing)
+                .with_system(run_timer_system.after(GameSystems::PlayerMovement)),
+        );
+        app.add_system_set(
+            SystemSet::on_enter(GameState::GameOver)
+                .with_system(spawn_game_over_screen.after(SaveSystems::Update)),
+        );
+        app.add_system_set(SystemSet::on_update(GameState::GameOver).with_system(game_over_input));
+        app.add_system_set(
+            SystemSet::on_exit(GameState::GameOver).with_system(despawn_game_over_screen),
+        );
+    }
+}
+
+/// Seconds from sunrise to sunset
+pub const DAY_LENGTH: f32 = 60.;
+
+/// Physics steps from sunrise to sunset. The bird stops moving after these, whatever the frame rate
+pub const DAY_TICKS: u64 = DAY_LENGTH as u64 * PHYSICS_RATE;
+
+/// Landings diving onto a downslope and hitting it slower than this, along its normal, are perfect slides
+pub(crate) const PERFECT_IMPACT: f32 = 40.;
+/// Landings hitting the ground faster than this are bad landings
+const BAD_IMPACT: f32 = 250.;
+
+/// Seconds the bird has to be in the air for touching down to count as a landing,
+/// so hopping over the crest of a hill isn't one
+const MIN_AIRTIME: f32 = 0.5;
+
+/// Perfect slides in a row it takes to get into fever
+const FEVER_STREAK: u32 = 3;
+
+const PERFECT_SLIDE_POINTS: u32 = 100;
+
+/// World units per meter of distance
+const UNITS_PER_METER: f32 = 100.;
+
+/// The progress of the current run
+#[derive(Default)]
+pub struct Run {
+    /// Seconds since sunrise
+    pub elapsed: f32,
+    /// Meters flown to the right
+    pub distance: f32,
+    /// Bonus points on top of the distance
+    pub points: u32,
+    pub landings: u32,
+    pub perfect_slides: u32,
+    pub bad_landings: u32,
+    /// Perfect slides in a row, reset by a bad landing
+    pub streak: u32,
+}
+
+impl Run {
+    pub fn score(&self) -> u32 {
+        self.distance as u32 + self.points
+    }
+
+    pub fn fever(&self) -> bool {
+        self.streak >= FEVER_STREAK
+    }
+
+    /// Seconds until sunset
+    pub fn time_left(&self) -> f32 {
+        (DAY_LENGTH - self.elapsed).max(0.)
+    }
+
+    /// Whether the sun has set
+    pub fn over(&self) -> bool {
+        self.elapsed >= DAY_TICKS as f32 * PHYSICS_STEP
+    }
+
+    /// Updates the time and distance after the physics ran up to `tick`, with the bird at `x`
+    pub fn advance(&mut self, tick: u64, x: f32) {
+        // Counted in physics steps, so replays end at the same moment
+        self.elapsed = tick.min(DAY_TICKS) as f32 * PHYSICS_STEP;
+        self.distance = self.distance.max(x / UNITS_PER_METER);
+    }
+
+    /// Rates a landing and scores it.
+    /// `None` if the bird wasn't in the air long enough for it to count
+    pub fn land(&mut self, touchdown: &Touchdown) -> Option<LandingQuality> {
+        if touchdown.airtime < MIN_AIRTIME {
+            return None;
+        }
+        let quality = LandingQuality::rate(touchdown);
+
+        self.landings += 1;
+        match quality {
+            LandingQuality::Perfect => {
+                self.perfect_slides += 1;
+                self.streak += 1;
+                self.points += PERFECT_SLIDE_POINTS * if self.fever() { 2 } else { 1 };
+            }
+            LandingQuality::Normal => {}
+            LandingQuality::Bad => {
+                self.bad_landings += 1;
+                self.streak = 0;
+            }
+        }
+
+        Some(quality)
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LandingQuality {
+    /// Dove gently onto the way down a hill
+    Perfect,
+    Normal,
+    /// Crashed into the hill
+    Bad,
+}
+
+impl LandingQuality {
+    fn rate(touchdown: &Touchdown) -> Self {
+        // The normal leans ahead where the ground falls away in front of the bird
+        let downslope = touchdown.ground_normal.x > 0.;
+
+        if touchdown.diving && downslope && touchdown.impact < PERFECT_IMPACT {
+            LandingQuality::Perfect
+        } else if touchdown.impact > BAD_IMPACT {
+            LandingQuality::Bad
+        } else {
+            LandingQuality::Normal
+        }
+    }
+}
+
+/// Sent when the bird lands, after rating the landing
+pub struct LandingEvent {
+    pub quality: LandingQuality,
+    /// Speed the bird hit the ground with, see [`Touchdown`]
+    pub impact: f32,
+}
+
+fn reset_run(mut restart_events: EventReader<RestartEvent>, mut run: ResMut<Run>) {
+    if restart_events.iter().count() > 0 {
+        *run = Run::default();
+    }
+}
+
+fn landing_system(
+    mut contact_events: EventReader<GroundContactEvent>,
+    mut landing_events: EventWriter<LandingEvent>,
+    mut run: ResMut<Run>,
+) {
+    for event in contact_events.iter() {
+        let GroundContactEvent::Landed(touchdown) = event else {
+            continue;
+        };
+        let Some(quality) = run.land(touchdown) else {
+            continue;
+        };
+
+        landing_events.send(LandingEvent {
+            quality,
+            impact: touchdown.impact,
+        });
+    }
+}
+
+fn run_timer_system(
+    mut run: ResMut<Run>,
+    mut state: ResMut<State<GameState>>,
+    player: Query<&PhysicsPose, With<PlayerComponent>>,
+    clock: Res<PhysicsClock>,
+) {
+    if let Ok(pose) = player.get_single() {
+        run.advance(clock.tick, pose.current.translation.x);
+    }
+
+    if run.over() {
+        let _ = state.set(GameState::GameOver);
+    }
+}
+
+// HUD
+
+#[derive(Component)]
+struct HudText;
+
+fn spawn_hud(mut commands: Commands, asset_server: Res<AssetServer>) {
+    commands
+        .spawn_bundle(TextBundle {
+            text: Text::with_section(
+                "",
+                TextStyle {
+                    font: asset_server.load("fonts/DejaVuSans.ttf"),
+                    font_size: 24.,
+                    color: Color::rgb(0.02, 0.39, 0.57),
+                },
+                Default::default(),
+            ),
+            style: Style {
+                position_type: PositionType::Absolute,
+                position: Rect {
+                    top: Val::Px(12.),
+                    left: Val::Px(16.),
+                    ..Default::default()
+                },
+                ..Default::default()
+            },
+            ..Default::default()
+        })
+        .insert(HudText);
+}
+
+fn hud_system(run: Res<Run>, mut texts: Query<&mut Text, With<HudText>>) {
+    if !run.is_changed() {
+        return;
+    }
+
+    for mut text in texts.iter_mut() {
+        text.sections[0].value = format!(
+            "{}{}   {:.0}s",
+            run.score(),
+            if run.fever() { "  FEVER" } else { "" },
+            run.time_left().ceil()
+        );
+    }
+}
+
+// Game over screen
+
+#[derive(Component)]
+struct GameOverScreen;
+
+/// Runs of the leaderboard shown on the game over screen
+const LEADERBOARD_LINES: usize = 5;
+
+fn spawn_game_over_screen(
+    mut commands: Commands,
+    asset_server: Res<AssetServer>,
+    run: Res<Run>,
+    save: Res<SaveData>,
+    terrain: Res<Terrain>,
+    latest: Res<LatestEntry>,
+    mode: Res<GameMode>,
+) {
+    let font = asset_server.load("fonts/DejaVuSans.ttf");
+
+    let text_style = |font_size: f32, color: Color| TextStyle {
+        font: font.clone(),
+        font_size,
+        color,
+    };
+
+    let mut lines = vec![
+        ("The sun has set".to_string(), 40., Color::WHITE),
+        (
+            format!(
+                "{:.0} m, {} perfect slides",
+                run.distance, run.perfect_slides
+            ),
+            22.,
+            Color::WHITE,
+        ),
+        (format!("Score: {}", run.score()), 30., Color::WHITE),
+        (format!("Best: {}", save.high_score), 18., Color::WHITE),
+    ];
+
+    if let GameMode::Daily { date } = &*mode {
+        lines.push((format!("Daily islands of {}", date), 18., Color::WHITE));
+    }
+
+    // The best runs on these hills, with this run highlighted if it made it
+    for (rank, entry) in save
+        .leaderboard
+        .ranking(terrain.seed)
+        .iter()
+        .enumerate()
+        .take(LEADERBOARD_LINES)
+    {
+        let color = if latest.rank == Some(rank) {
+            Color::rgb(1., 0.85, 0.3)
+        } else {
+            Color::rgba(1., 1., 1., 0.8)
+        };
+
+        lines.push((
+            format!(
+                "{}.  {}   {:.0} m   {}",
+                rank + 1,
+                entry.score,
+                entry.distance,
+                entry.date
+            ),
+            16.,
+            color,
+        ));
+    }
+
+    lines.push((
+        "R to fly again, Esc for the title screen".to_string(),
+        16.,
+        Color::WHITE,
+    ));
+
+    commands
+        .spawn_bundle(NodeBundle {
+            style: Style {
+                size: Size::new(Val::Percent(100.), Val::Percent(100.)),
+                position_type: PositionType::Absolute,
+                flex_direction: FlexDirection::ColumnReverse,
+                justify_content: JustifyContent::Center,
+                align_items: AlignItems::Center,
+                ..Default::default()
+            },
+            color: Color::rgba(0.1, 0.05, 0.2, 0.5).into(),
+            ..Default::default()
+        })
+        .insert(GameOverScreen)
+        .with_children(|screen| {
+            for (line, font_size, color) in lines {
+                screen.spawn_bundle(TextBundle {
+                    text: Text::with_section(
+                        line,
+                        text_style(font_size, color),
+                        Default::default(),
+                    ),
+                    style: Style {
+                        margin: Rect::all(Val::Px(8.)),
+                        ..Default::default()
+                    },
+                    ..Default::default()
+                });
+            }
+        });
+}
+
+fn game_over_input(
+    mut actions: ResMut<ActionState>,
+    mut state: ResMut<State<GameState>>,
+    mut restart_events: EventWriter<RestartEvent>,
+) {
+    if actions.consume(Action::Restart) {
+        restart_events.send(RestartEvent);
+        let _ = state.set(GameState::Resuming);
+    } else if actions.consume(Action::Pause) {
+        restart_events.send(RestartEvent);
+        let _ = state.set(GameState::Title);
+    }
+}
+
+fn despawn_game_over_screen(mut commands: Commands, screens: Query<Entity, With<GameOverScreen>>) {
+    for screen in screens.iter() {
+        commands.entity(screen).despawn_recursive();
+    }
+}
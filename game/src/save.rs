@@ -0,0 +1,196 @@
+//! The save file, holding the player's records and progress.
+//! It carries a version, so files written by older versions of the game can be upgraded when loaded
+
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    autopilot::Autopilot,
+    leaderboard::Leaderboard,
+    replay::ReplayPlayback,
+    run::Run,
+    skin::{SelectedSkin, SkinChanged},
+    storage, GameState,
+};
+
+/// Loads the save file when the app is built, and writes it whenever it changes.
+/// Has to be added before the plugins reading [`SaveData`] while being built
+pub struct SavePlugin;
+
+impl Plugin for SavePlugin {
+    fn build(&self, app: &mut App) {
+        app.insert_resource(load());
+        app.add_system(remember_selected_skin.label(SaveSystems::Update));
+        app.add_system(write_save.after(SaveSystems::Update));
+        app.add_system_set(
+            SystemSet::on_enter(GameState::GameOver)
+                .with_system(record_run.label(SaveSystems::Update)),
+        );
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash, SystemLabel)]
+pub enum SaveSystems {
+    /// Systems changing the [`SaveData`]
+    Update,
+}
+
+/// Version of the save file written by this version of the game
+const SAVE_VERSION: u32 = 2;
+
+const SAVE_NAME: &str = "save";
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SaveData {
+    version: u32,
+    pub high_score: u32,
+    /// Meters
+    pub best_distance: f32,
+    /// Days played until sunset
+    pub runs_played: u32,
+    pub selected_skin: String,
+    pub leaderboard: Leaderboard,
+    /// Set when the file was written by a newer version of the game, which this one must not overwrite
+    #[serde(skip)]
+    read_only: bool,
+}
+
+impl Default for SaveData {
+    fn default() -> Self {
+        Self {
+            version: SAVE_VERSION,
+            high_score: 0,
+            best_distance: 0.,
+            runs_played: 0,
+            selected_skin: SelectedSkin::default().name,
+            leaderboard: Leaderboard::default(),
+            read_only: false,
+        }
+    }
+}
+
+/// Save file version 1, from before the leaderboard
+#[derive(Deserialize)]
+struct SaveV1 {
+    high_score: u32,
+    best_distance: f32,
+    runs_played: u32,
+    selected_skin: String,
+}
+
+impl From<SaveV1> for SaveData {
+    fn from(save: SaveV1) -> Self {
+        Self {
+            version: SAVE_VERSION,
+            high_score: save.high_score,
+            best_distance: save.best_distance,
+            runs_played: save.runs_played,
+            selected_skin: save.selected_skin,
+            leaderboard: Leaderboard::default(),
+            read_only: false,
+        }
+    }
+}
+
+/// Just the version of a save file, to know how to read the rest of it
+#[derive(Deserialize)]
+struct SaveHeader {
+    version: u32,
+}
+
+fn load() -> SaveData {
+    let Some(contents) = storage::read(SAVE_NAME) else {
+        return migrate_legacy_files();
+    };
+
+    // Going back to an older version of the game must not lose the progress made in the newer one
+    if let Ok(SaveHeader { version }) = ron::from_str(&contents) {
+        if version > SAVE_VERSION {
+            warn!(
+                "the save file is from a newer version of the game (version {}, this is version {}), \
+                 progress won't be saved",
+                version, SAVE_VERSION
+            );
+            return SaveData {
+                read_only: true,
+                ..Default::default()
+            };
+        }
+    }
+
+    match parse(&contents) {
+        Ok(save) => save,
+        Err(err) => {
+            // Keep the unreadable file around instead of overwriting it with the next save
+            warn!(
+                "starting with a new save, the save file is unreadable: {}",
+                err
+            );
+            let backup = format!("{}.corrupted", SAVE_NAME);
+            if let Err(err) = storage::write(&backup, &contents) {
+                error!("failed to back up the unreadable save file: {}", err);
+            }
+
+            SaveData::default()
+        }
+    }
+}
+
+fn parse(contents: &str) -> anyhow::Result<SaveData> {
+    let header: SaveHeader = ron::from_str(contents)?;
+
+    // Older versions are read in their own format here, and upgraded step by step
+    match header.version {
+        1 => Ok(ron::from_str::<SaveV1>(contents)?.into()),
+        SAVE_VERSION => Ok(ron::from_str(contents)?),
+        version => Err(anyhow::anyhow!(
+            "save file version {} is not supported by this version of the game (version {})",
+            version,
+            SAVE_VERSION
+        )),
+    }
+}
+
+/// Before the save file existed, only the selected skin was stored, in a file of its own
+fn migrate_legacy_files() -> SaveData {
+    let mut save = SaveData::default();
+
+    if let Some(skin) = storage::load::<SelectedSkin>("skin") {
+        info!("moving the selected skin into the save file");
+        save.selected_skin = skin.name;
+        storage::save(SAVE_NAME, &save);
+    }
+
+    save
+}
+
+// Only skins the bird actually wore are remembered, not names missing from the skins file
+fn remember_selected_skin(mut skin_changed: EventReader<SkinChanged>, mut save: ResMut<SaveData>) {
+    if let Some(SkinChanged { skin }) = skin_changed.iter().last() {
+        if save.selected_skin != skin.name {
+            save.selected_skin = skin.name.clone();
+        }
+    }
+}
+
+fn record_run(
+    run: Res<Run>,
+    mut save: ResMut<SaveData>,
+    playback: Option<Res<ReplayPlayback>>,
+    autopilot: Option<Res<Autopilot>>,
+) {
+    // Watching a replay or the autopilot doesn't count as playing
+    if playback.is_some() || autopilot.is_some() {
+        return;
+    }
+
+    save.runs_played += 1;
+    save.high_score = save.high_score.max(run.score());
+    save.best_distance = save.best_distance.max(run.distance);
+}
+
+fn write_save(save: Res<SaveData>) {
+    if save.is_changed() && !save.is_added() && !save.read_only {
+        storage::save(SAVE_NAME, &*save);
+    }
+}
@@ -0,0 +1,171 @@
+//! A run of the game without Bevy's app: the bird flying over the terrain in physics steps, and the scoring.
+//! Steps and scores the same way the game does, so runs can be simulated without a window
+
+use std::path::Path;
+
+use bevy::prelude::*;
+use serde::Serialize;
+
+use crate::{
+    autopilot::Autopilot,
+    player::{start_transform, step_player, GroundContactEvent, PlayerComponent, PHYSICS_STEP},
+    replay::{self, Replay},
+    run::{Run, DAY_TICKS},
+    terrain::{self, Terrain},
+    tuning::GameTuning,
+    GameConfig,
+};
+
+pub struct Simulation {
+    pub terrain: Terrain,
+    pub tuning: GameTuning,
+    pub transform: Transform,
+    pub player: PlayerComponent,
+    pub run: Run,
+    /// Physics steps taken so far
+    pub tick: u64,
+}
+
+impl Simulation {
+    pub fn new(seed: u32, tuning: GameTuning) -> Self {
+        Self {
+            terrain: Terrain::new(seed, tuning.hill_scale),
+            transform: start_transform(),
+            player: PlayerComponent::new(&tuning),
+            run: Run::default(),
+            tick: 0,
+            tuning,
+        }
+    }
+
+    /// Whether the sun has set
+    pub fn finished(&self) -> bool {
+        self.tick >= DAY_TICKS
+    }
+
+    /// Takes one physics step, with the bird diving or not
+    pub fn step(&mut self, diving: bool) {
+        self.tick += 1;
+        self.player.diving = diving;
+
+        let contact = step_player(
+            &mut self.transform,
+            &mut self.player,
+            &self.terrain,
+            &self.tuning,
+            PHYSICS_STEP,
+        );
+        if let Some(GroundContactEvent::Landed(touchdown)) = contact {
+            self.run.land(&touchdown);
+        }
+
+        self.run.advance(self.tick, self.transform.translation.x);
+    }
+
+    /// Steps until sunset, diving as the policy decides
+    pub fn run_until_sunset(&mut self, policy: &mut dyn DivePolicy) {
+        while !self.finished() {
+            let diving = policy.dive(self);
+            self.step(diving);
+        }
+    }
+
+    pub fn summary(&self) -> RunSummary {
+        RunSummary {
+            seed: self.terrain.seed,
+            score: self.run.score(),
+            distance: self.run.distance,
+            landings: self.run.landings,
+            perfect_slides: self.run.perfect_slides,
+            bad_landings: self.run.bad_landings,
+        }
+    }
+}
+
+/// How a run went
+#[derive(Debug, Clone, Serialize)]
+pub struct RunSummary {
+    pub seed: u32,
+    pub score: u32,
+    /// Meters
+    pub distance: f32,
+    pub landings: u32,
+    pub perfect_slides: u32,
+    pub bad_landings: u32,
+}
+
+/// Stands in for the player, deciding whether the bird dives during the next step
+pub trait DivePolicy {
+    fn dive(&mut self, sim: &Simulation) -> bool;
+}
+
+/// Never dives
+pub struct Glide;
+
+impl DivePolicy for Glide {
+    fn dive(&mut self, _sim: &Simulation) -> bool {
+        false
+    }
+}
+
+/// Dives whenever the bird is in the air and falling, the simplest way to gain speed
+pub struct DiveWhileFalling;
+
+impl DivePolicy for DiveWhileFalling {
+    fn dive(&mut self, sim: &Simulation) -> bool {
+        !sim.player.grounded && sim.player.velocity.y < 0.
+    }
+}
+
+impl DivePolicy for Autopilot {
+    fn dive(&mut self, sim: &Simulation) -> bool {
+        Autopilot::dive(self, &sim.transform, &sim.player, &sim.terrain, &sim.tuning)
+    }
+}
+
+impl DivePolicy for Replay {
+    fn dive(&mut self, sim: &Simulation) -> bool {
+        self.diving_at(sim.tick + 1)
+    }
+}
+
+/// Tuning used when there is no asset server to load it, falling back to the built in values
+pub fn load_tuning(path: &Path) -> GameTuning {
+    GameTuning::from_file(path).unwrap_or_else(|err| {
+        eprintln!(
+            "using the default tuning, {} is unreadable: {}",
+            path.display(),
+            err
+        );
+        GameTuning::default()
+    })
+}
+
+/// Simulates a run from `config` until sunset and prints how it went.
+/// The bird follows the replay of the config if there is one, otherwise it never dives
+pub fn run_headless(config: &GameConfig) {
+    let replay: Option<Replay> = config.replay.as_ref().map(|path| {
+        replay::load_replay_file(path).unwrap_or_else(|err| {
+            eprintln!("failed to read replay {}: {}", path.display(), err);
+            std::process::exit(1);
+        })
+    });
+    let seed = match &replay {
+        Some(replay) => replay.seed,
+        None => config.seed.unwrap_or_else(terrain::random_seed),
+    };
+
+    let mut policy: Box<dyn DivePolicy> = match replay {
+        Some(replay) => Box::new(replay),
+        None => Box::new(Glide),
+    };
+
+    let mut sim = Simulation::new(seed, load_tuning(Path::new("assets/game.tuning.ron")));
+    sim.run_until_sunset(policy.as_mut());
+
+    let summary = sim.summary();
+    println!(
+        "seed {}: score {}, {:.0} m, {} landings, {} perfect slides",
+        summary.seed, summary.score, summary.distance, summary.landings, summary.perfect_slides
+    );
+}
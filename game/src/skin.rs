@@ -0,0 +1,656 @@
+use bevy::{
+    asset::{AssetLoader, LoadContext, LoadedAsset},
+    ecs::system::lifetimeless::SRes,
+    prelude::*,
+    reflect::TypeUuid,
+    render::{
+        render_asset::{PrepareAssetError, RenderAsset, RenderAssets},
+        render_resource::{
+            BindGroup, BindGroupDescriptor, BindGroupEntry, BindGroupLayoutDescriptor,
+            BindGroupLayoutEntry, BindingResource, BindingType, RenderPipelineDescriptor,
+            SamplerBindingType, ShaderStages, TextureSampleType, TextureViewDimension,
+        },
+        renderer::RenderDevice,
+    },
+    sprite::{Material2dPipeline, Material2dPlugin, MaterialMesh2dBundle, SpecializedMaterial2d},
+    utils::BoxedFuture,
+};
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    animation::{SpriteAnimation, SpriteSheet},
+    input::{Action, ActionState, InputMap},
+    player::PlayerComponent,
+    save::SaveData,
+    trail::Trail,
+    GameState,
+};
+
+/// Selectable appearances of the bird, loaded from `assets/bird.skins.ron`.
+/// Press Tab to open the skin selection screen, or B to switch to the next skin,
+/// unless rebound
+pub struct SkinPlugin;
+
+impl Plugin for SkinPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_asset::<SkinRegistry>();
+        app.init_asset_loader::<SkinRegistryLoader>();
+        app.add_plugin(Material2dPlugin::<SkinMaterial>::default());
+        let selected = app
+            .world
+            .get_resource::<SaveData>()
+            .map(|save| SelectedSkin {
+                name: save.selected_skin.clone(),
+            })
+            .unwrap_or_default();
+        app.insert_resource(selected);
+        app.add_startup_system(load_skins);
+        app.add_event::<SkinChanged>();
+        app.add_system(skin_change_events.label(SkinSystems::ChangeEvents));
+        app.add_system(apply_skin_visual.after(SkinSystems::ChangeEvents));
+        app.add_system(apply_skin_trail.after(SkinSystems::ChangeEvents));
+        app.add_system_set(SystemSet::on_update(GameState::Playing).with_system(skin_hotkeys));
+        app.add_system_set(
+            SystemSet::on_enter(GameState::SkinSelect).with_system(spawn_skin_select_screen),
+        );
+        app.add_system_set(
+            SystemSet::on_update(GameState::SkinSelect)
+                .with_system(skin_select_input)
+                .with_system(skin_select_buttons)
+                .with_system(skin_select_highlight),
+        );
+        app.add_system_set(
+            SystemSet::on_exit(GameState::SkinSelect).with_system(despawn_skin_select_screen),
+        );
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash, SystemLabel)]
+enum SkinSystems {
+    ChangeEvents,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct Skin {
+    pub name: String,
+    pub texture: String,
+    pub size: (f32, f32),
+    #[serde(default)]
+    pub trail: Option<SkinTrail>,
+    /// Fragment shader used to draw the texture, instead of drawing it as a plain sprite
+    #[serde(default)]
+    pub shader: Option<String>,
+    /// Animated sprite sheet used in game instead of the texture, the texture is still shown on the selection screen
+    #[serde(default)]
+    pub animation: Option<SpriteSheet>,
+}
+
+/// Settings for the [`Trail`] behind the bird
+#[derive(Debug, Clone, Deserialize)]
+pub struct SkinTrail {
+    pub segments: usize,
+    pub spacing: f32,
+    pub fade: f32,
+    pub width: f32,
+    pub color: (f32, f32, f32, f32),
+}
+
+impl From<&SkinTrail> for Trail {
+    fn from(trail: &SkinTrail) -> Self {
+        let (r, g, b, a) = trail.color;
+
+        Trail {
+            segments: trail.segments,
+            spacing: trail.spacing,
+            fade: trail.fade,
+            width: trail.width,
+            color: Color::rgba(r, g, b, a),
+        }
+    }
+}
+
+#[derive(Debug, Deserialize, TypeUuid)]
+#[uuid = "0b0a3c6e-6f8e-4d53-a5a4-3f1b6e9f2c77"]
+pub struct SkinRegistry {
+    pub skins: Vec<Skin>,
+}
+
+impl SkinRegistry {
+    fn position(&self, name: &str) -> Option<usize> {
+        self.skins.iter().position(|skin| skin.name == name)
+    }
+
+    /// The skin named `name`, falling back to the first skin if it doesn't exist
+    pub fn get(&self, name: &str) -> Option<&Skin> {
+        self.skins.get(self.position(name).unwrap_or(0))
+    }
+}
+
+#[derive(Default)]
+pub struct SkinRegistryLoader;
+
+impl AssetLoader for SkinRegistryLoader {
+    fn load<'a>(
+        &'a self,
+        bytes: &'a [u8],
+        load_context: &'a mut LoadContext,
+    ) -> BoxedFuture<'a, Result<(), anyhow::Error>> {
+        Box::pin(async move {
+            let registry: SkinRegistry = ron::de::from_bytes(bytes)?;
+            load_context.set_default_asset(LoadedAsset::new(registry));
+            Ok(())
+        })
+    }
+
+    // Bevy matches extensions against what follows each dot in the file name,
+    // so the file needs a name like `bird.skins.ron`
+    fn extensions(&self) -> &[&str] {
+        &["skins.ron"]
+    }
+}
+
+pub struct Skins(Handle<SkinRegistry>);
+
+/// Name of the skin the player has picked, it is remembered between runs of the game in the [`SaveData`]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SelectedSkin {
+    pub name: String,
+}
+
+impl Default for SelectedSkin {
+    fn default() -> Self {
+        Self {
+            name: "Tiny Wings".to_string(),
+        }
+    }
+}
+
+/// The entity drawing the current skin, a child of the player
+#[derive(Component)]
+pub struct PlayerVisual;
+
+fn load_skins(mut commands: Commands, asset_server: Res<AssetServer>) {
+    commands.insert_resource(Skins(asset_server.load("bird.skins.ron")));
+}
+
+/// Sent when the bird should change its look, because another skin was selected or the skins file was (re)loaded
+pub struct SkinChanged {
+    pub skin: Skin,
+}
+
+fn skin_change_events(
+    mut selected: ResMut<SelectedSkin>,
+    skins: Res<Skins>,
+    registries: Res<Assets<SkinRegistry>>,
+    mut registry_events: EventReader<AssetEvent<SkinRegistry>>,
+    mut skin_changed: EventWriter<SkinChanged>,
+) {
+    let mut registry_changed = false;
+    for event in registry_events.iter() {
+        if let AssetEvent::Created { handle } | AssetEvent::Modified { handle } = event {
+            registry_changed |= *handle == skins.0;
+        }
+    }
+
+    if !selected.is_changed() && !registry_changed {
+        return;
+    }
+
+    let Some(registry) = registries.get(&skins.0) else {
+        return;
+    };
+
+    // The name can come from an old save file or the command line
+    if registry.position(&selected.name).is_none() {
+        let default = SelectedSkin::default();
+        warn!(
+            "there is no skin named {:?}, wearing {:?} instead",
+            selected.name, default.name
+        );
+        *selected = default;
+    }
+
+    if let Some(skin) = registry.get(&selected.name) {
+        skin_changed.send(SkinChanged { skin: skin.clone() });
+    }
+}
+
+type SkinAssets<'w> = (
+    ResMut<'w, Assets<Mesh>>,
+    ResMut<'w, Assets<SkinMaterial>>,
+    ResMut<'w, Assets<TextureAtlas>>,
+);
+
+// Replace the sprite of the bird
+fn apply_skin_visual(
+    mut commands: Commands,
+    mut skin_changed: EventReader<SkinChanged>,
+    players: Query<(Entity, Option<&Children>), With<PlayerComponent>>,
+    visuals: Query<Entity, With<PlayerVisual>>,
+    asset_server: Res<AssetServer>,
+    mut assets: SkinAssets,
+) {
+    let Some(SkinChanged { skin }) = skin_changed.iter().last() else {
+        return;
+    };
+
+    for (player, children) in players.iter() {
+        for &child in children.iter().flat_map(|children| children.iter()) {
+            if visuals.get(child).is_ok() {
+                commands.entity(child).despawn_recursive();
+            }
+        }
+
+        commands.entity(player).with_children(|parent| {
+            spawn_skin_visual(parent, skin, &asset_server, &mut assets);
+        });
+    }
+}
+
+// Only skins with a trail keep the trail entities around, skins without one cost nothing per frame
+fn apply_skin_trail(
+    mut commands: Commands,
+    mut skin_changed: EventReader<SkinChanged>,
+    players: Query<Entity, With<PlayerComponent>>,
+) {
+    let Some(SkinChanged { skin }) = skin_changed.iter().last() else {
+        return;
+    };
+
+    for player in players.iter() {
+        match &skin.trail {
+            Some(trail) => {
+                commands.entity(player).insert(Trail::from(trail));
+            }
+            // The trail plugin cleans up the ribbon once the trail is gone
+            None => {
+                commands.entity(player).remove::<Trail>();
+            }
+        }
+    }
+}
+
+fn spawn_skin_visual(
+    parent: &mut ChildBuilder,
+    skin: &Skin,
+    asset_server: &AssetServer,
+    (meshes, materials, atlases): &mut SkinAssets,
+) {
+    let texture = asset_server.load(skin.texture.as_str());
+    let size = Vec2::new(skin.size.0, skin.size.1);
+
+    if let Some(shader) = &skin.shader {
+        parent
+            .spawn_bundle(MaterialMesh2dBundle {
+                mesh: meshes.add(Mesh::from(shape::Quad::new(size))).into(),
+                material: materials.add(SkinMaterial {
+                    texture,
+                    shader: asset_server.load(shader.as_str()),
+                }),
+                ..Default::default()
+            })
+            .insert(PlayerVisual);
+    } else if let Some(sheet) = &skin.animation {
+        parent
+            .spawn_bundle(SpriteSheetBundle {
+                texture_atlas: atlases.add(sheet.texture_atlas(asset_server)),
+                sprite: TextureAtlasSprite {
+                    custom_size: Some(size),
+                    ..Default::default()
+                },
+                ..Default::default()
+            })
+            .insert(SpriteAnimation::new(sheet))
+            .insert(PlayerVisual);
+    } else {
+        parent
+            .spawn_bundle(SpriteBundle {
+                texture,
+                sprite: Sprite {
+                    custom_size: Some(size),
+                    ..Default::default()
+                },
+                ..Default::default()
+            })
+            .insert(PlayerVisual);
+    }
+}
+
+fn skin_hotkeys(
+    mut actions: ResMut<ActionState>,
+    mut state: ResMut<State<GameState>>,
+    mut selected: ResMut<SelectedSkin>,
+    skins: Res<Skins>,
+    registries: Res<Assets<SkinRegistry>>,
+) {
+    if actions.just_pressed(Action::ToggleSkin) {
+        if let Some(registry) = registries.get(&skins.0) {
+            let next =
+                registry.position(&selected.name).map_or(0, |i| i + 1) % registry.skins.len();
+            selected.name = registry.skins[next].name.clone();
+        }
+    }
+
+    if actions.consume(Action::SkinSelect) {
+        let _ = state.set(GameState::SkinSelect);
+    }
+}
+
+// Skin selection screen
+
+#[derive(Component)]
+struct SkinSelectScreen;
+
+#[derive(Component)]
+struct SkinButton(usize);
+
+/// The skin currently highlighted on the selection screen
+struct SkinCursor(usize);
+
+const BUTTON_COLOR: Color = Color::rgba(1., 1., 1., 0.15);
+const BUTTON_HIGHLIGHT_COLOR: Color = Color::rgba(1., 1., 1., 0.45);
+
+fn spawn_skin_select_screen(
+    mut commands: Commands,
+    asset_server: Res<AssetServer>,
+    selected: Res<SelectedSkin>,
+    skins: Res<Skins>,
+    registries: Res<Assets<SkinRegistry>>,
+    input_map: Res<InputMap>,
+) {
+    let font = asset_server.load("fonts/DejaVuSans.ttf");
+    let skins: &[Skin] = registries
+        .get(&skins.0)
+        .map_or(&[], |registry| &registry.skins);
+
+    commands.insert_resource(SkinCursor(
+        skins
+            .iter()
+            .position(|skin| skin.name == selected.name)
+            .unwrap_or(0),
+    ));
+
+    let text_style = |font_size: f32| TextStyle {
+        font: font.clone(),
+        font_size,
+        color: Color::WHITE,
+    };
+
+    commands
+        .spawn_bundle(NodeBundle {
+            style: Style {
+                size: Size::new(Val::Percent(100.), Val::Percent(100.)),
+                position_type: PositionType::Absolute,
+                flex_direction: FlexDirection::ColumnReverse,
+                justify_content: JustifyContent::Center,
+                align_items: AlignItems::Center,
+                ..Default::default()
+            },
+            color: Color::rgba(0., 0., 0., 0.6).into(),
+            ..Default::default()
+        })
+        .insert(SkinSelectScreen)
+        .with_children(|screen| {
+            screen.spawn_bundle(TextBundle {
+                text: Text::with_section("Choose your bird", text_style(40.), Default::default()),
+                ..Default::default()
+            });
+
+            screen
+                .spawn_bundle(NodeBundle {
+                    style: Style {
+                        margin: Rect::all(Val::Px(24.)),
+                        ..Default::default()
+                    },
+                    color: Color::NONE.into(),
+                    ..Default::default()
+                })
+                .with_children(|row| {
+                    for (i, skin) in skins.iter().enumerate() {
+                        row.spawn_bundle(ButtonBundle {
+                            style: Style {
+                                size: Size::new(Val::Px(140.), Val::Px(140.)),
+                                margin: Rect::all(Val::Px(8.)),
+                                flex_direction: FlexDirection::ColumnReverse,
+                                justify_content: JustifyContent::SpaceEvenly,
+                                align_items: AlignItems::Center,
+                                ..Default::default()
+                            },
+                            color: BUTTON_COLOR.into(),
+                            ..Default::default()
+                        })
+                        .insert(SkinButton(i))
+                        .with_children(|button| {
+                            button.spawn_bundle(ImageBundle {
+                                style: Style {
+                                    size: Size::new(Val::Px(skin.size.0), Val::Px(skin.size.1)),
+                                    ..Default::default()
+                                },
+                                image: asset_server.load(skin.texture.as_str()).into(),
+                                ..Default::default()
+                            });
+                            button.spawn_bundle(TextBundle {
+                                text: Text::with_section(
+                                    skin.name.clone(),
+                                    text_style(18.),
+                                    Default::default(),
+                                ),
+                                ..Default::default()
+                            });
+                        });
+                    }
+                });
+
+            screen.spawn_bundle(TextBundle {
+                text: Text::with_section(
+                    format!(
+                        "{}/{} to browse, {} to pick, {} to go back",
+                        input_map.label(Action::Previous),
+                        input_map.label(Action::Next),
+                        input_map.label(Action::Confirm),
+                        input_map.label(Action::Pause),
+                    ),
+                    text_style(16.),
+                    Default::default(),
+                ),
+                ..Default::default()
+            });
+        });
+}
+
+fn skin_select_input(
+    mut actions: ResMut<ActionState>,
+    mut state: ResMut<State<GameState>>,
+    mut cursor: ResMut<SkinCursor>,
+    mut selected: ResMut<SelectedSkin>,
+    skins: Res<Skins>,
+    registries: Res<Assets<SkinRegistry>>,
+) {
+    if actions.consume(Action::Pause) {
+        let _ = state.set(GameState::Playing);
+        return;
+    }
+
+    let Some(registry) = registries.get(&skins.0) else {
+        return;
+    };
+    let count = registry.skins.len();
+
+    if count == 0 {
+        return;
+    }
+
+    if actions.just_pressed(Action::Previous) {
+        cursor.0 = (cursor.0 + count - 1) % count;
+    }
+    if actions.just_pressed(Action::Next) {
+        cursor.0 = (cursor.0 + 1) % count;
+    }
+
+    if actions.consume(Action::Confirm) {
+        if let Some(skin) = registry.skins.get(cursor.0) {
+            selected.name = skin.name.clone();
+        }
+        let _ = state.set(GameState::Playing);
+    }
+}
+
+fn skin_select_buttons(
+    buttons: Query<(&Interaction, &SkinButton), Changed<Interaction>>,
+    mut state: ResMut<State<GameState>>,
+    mut cursor: ResMut<SkinCursor>,
+    mut selected: ResMut<SelectedSkin>,
+    skins: Res<Skins>,
+    registries: Res<Assets<SkinRegistry>>,
+) {
+    for (interaction, button) in buttons.iter() {
+        match interaction {
+            Interaction::Hovered => cursor.0 = button.0,
+            Interaction::Clicked => {
+                if let Some(skin) = registries
+                    .get(&skins.0)
+                    .and_then(|registry| registry.skins.get(button.0))
+                {
+                    selected.name = skin.name.clone();
+                }
+                let _ = state.set(GameState::Playing);
+            }
+            Interaction::None => {}
+        }
+    }
+}
+
+fn skin_select_highlight(cursor: Res<SkinCursor>, mut buttons: Query<(&SkinButton, &mut UiColor)>) {
+    if !cursor.is_changed() {
+        return;
+    }
+
+    for (button, mut color) in buttons.iter_mut() {
+        *color = if button.0 == cursor.0 {
+            BUTTON_HIGHLIGHT_COLOR
+        } else {
+            BUTTON_COLOR
+        }
+        .into();
+    }
+}
+
+fn despawn_skin_select_screen(
+    mut commands: Commands,
+    screens: Query<Entity, With<SkinSelectScreen>>,
+) {
+    for screen in screens.iter() {
+        commands.entity(screen).despawn_recursive();
+    }
+    commands.remove_resource::<SkinCursor>();
+}
+
+/// Material drawing the skin texture on a quad with a custom fragment shader
+#[derive(Debug, Clone, TypeUuid)]
+#[uuid = "9d3f6a51-2c7e-4b1a-8e0d-7a5c4f2b9e13"]
+pub struct SkinMaterial {
+    pub texture: Handle<Image>,
+    pub shader: Handle<Shader>,
+}
+
+pub struct GpuSkinMaterial {
+    bind_group: BindGroup,
+    shader: Handle<Shader>,
+}
+
+impl RenderAsset for SkinMaterial {
+    type ExtractedAsset = SkinMaterial;
+
+    type PreparedAsset = GpuSkinMaterial;
+
+    type Param = (
+        SRes<RenderDevice>,
+        SRes<Material2dPipeline<SkinMaterial>>,
+        SRes<RenderAssets<Image>>,
+    );
+
+    fn extract_asset(&self) -> Self::ExtractedAsset {
+        self.clone()
+    }
+
+    fn prepare_asset(
+        material: Self::ExtractedAsset,
+        (render_device, skin_pipeline, gpu_images): &mut bevy::ecs::system::SystemParamItem<
+            Self::Param,
+        >,
+    ) -> Result<Self::PreparedAsset, PrepareAssetError<Self::ExtractedAsset>> {
+        let (texture_view, sampler) = if let Some(gpu_image) = gpu_images.get(&material.texture) {
+            (&gpu_image.texture_view, &gpu_image.sampler)
+        } else {
+            return Err(PrepareAssetError::RetryNextUpdate(material));
+        };
+
+        let bind_group = render_device.create_bind_group(&BindGroupDescriptor {
+            entries: &[
+                BindGroupEntry {
+                    binding: 0,
+                    resource: BindingResource::TextureView(texture_view),
+                },
+                BindGroupEntry {
+                    binding: 1,
+                    resource: BindingResource::Sampler(sampler),
+                },
+            ],
+            label: Some("skin_material_bind_group"),
+            layout: &skin_pipeline.material2d_layout,
+        });
+
+        Ok(GpuSkinMaterial {
+            bind_group,
+            shader: material.shader,
+        })
+    }
+}
+
+// A SpecializedMaterial2d is used, since the fragment shader is picked per material rather than per material type
+impl SpecializedMaterial2d for SkinMaterial {
+    fn bind_group(
+        render_asset: &<Self as bevy::render::render_asset::RenderAsset>::PreparedAsset,
+    ) -> &bevy::render::render_resource::BindGroup {
+        &render_asset.bind_group
+    }
+
+    fn bind_group_layout(
+        render_device: &bevy::render::renderer::RenderDevice,
+    ) -> bevy::render::render_resource::BindGroupLayout {
+        render_device.create_bind_group_layout(&BindGroupLayoutDescriptor {
+            label: Some("skin_material_layout"),
+            entries: &[
+                // Texture
+                BindGroupLayoutEntry {
+                    binding: 0,
+                    visibility: ShaderStages::FRAGMENT,
+                    ty: BindingType::Texture {
+                        multisampled: false,
+                        sample_type: TextureSampleType::Float { filterable: true },
+                        view_dimension: TextureViewDimension::D2,
+                    },
+                    count: None,
+                },
+                // Texture Sampler
+                BindGroupLayoutEntry {
+                    binding: 1,
+                    visibility: ShaderStages::FRAGMENT,
+                    ty: BindingType::Sampler(SamplerBindingType::Filtering),
+                    count: None,
+                },
+            ],
+        })
+    }
+
+    type Key = Handle<Shader>;
+    fn key(material: &<Self as RenderAsset>::PreparedAsset) -> Self::Key {
+        material.shader.clone()
+    }
+
+    fn specialize(key: Self::Key, descriptor: &mut RenderPipelineDescriptor) {
+        if let Some(fragment) = descriptor.fragment.as_mut() {
+            fragment.shader = key;
+        }
+    }
+}
@@ -0,0 +1,150 @@
+use bevy::{
+    audio::{Audio, AudioSink, AudioSource, PlaybackSettings},
+    prelude::*,
+};
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    player::PlayerComponent,
+    run::{LandingEvent, LandingQuality},
+    storage, GameState, GameSystems,
+};
+
+/// Wind rushing past the bird, louder the faster it flies, and one-shot sounds for what happens during a run.
+/// All volumes go through the [`Mixer`]
+pub struct SoundPlugin;
+
+impl Plugin for SoundPlugin {
+    fn build(&self, app: &mut App) {
+        app.insert_resource(storage::load::<Mixer>("mixer").unwrap_or_default());
+        app.add_startup_system(load_sounds);
+        app.add_system(save_mixer);
+        app.add_system(wind_system.after(GameSystems::PlayerMovement));
+        app.add_system(landing_sounds);
+        app.add_system_set(SystemSet::on_enter(GameState::GameOver).with_system(game_over_sound));
+    }
+}
+
+/// Volumes from 0 to 1, saved when changed
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Mixer {
+    pub master: f32,
+    pub music: f32,
+    pub sfx: f32,
+}
+
+impl Default for Mixer {
+    fn default() -> Self {
+        Self {
+            master: 1.,
+            music: 0.7,
+            sfx: 1.,
+        }
+    }
+}
+
+impl Mixer {
+    pub fn music_volume(&self) -> f32 {
+        self.master * self.music
+    }
+
+    pub fn sfx_volume(&self) -> f32 {
+        self.master * self.sfx
+    }
+}
+
+fn save_mixer(mixer: Res<Mixer>) {
+    if mixer.is_changed() && !mixer.is_added() {
+        storage::save("mixer", &*mixer);
+    }
+}
+
+struct Sounds {
+    land: Handle<AudioSource>,
+    perfect_slide: Handle<AudioSource>,
+    bad_landing: Handle<AudioSource>,
+    game_over: Handle<AudioSource>,
+}
+
+/// The looping wind sound
+struct Wind(Handle<AudioSink>);
+
+/// Speeds at which the wind starts to pick up and is the loudest
+const WIND_SPEED_RANGE: (f32, f32) = (150., 800.);
+
+fn load_sounds(
+    mut commands: Commands,
+    asset_server: Res<AssetServer>,
+    audio: Res<Audio>,
+    sinks: Res<Assets<AudioSink>>,
+) {
+    let wind = audio.play_with_settings(
+        asset_server.load("sounds/wind.wav"),
+        PlaybackSettings::LOOP.with_volume(0.),
+    );
+    commands.insert_resource(Wind(sinks.get_handle(wind)));
+
+    commands.insert_resource(Sounds {
+        land: asset_server.load("sounds/land.wav"),
+        perfect_slide: asset_server.load("sounds/perfect-slide.wav"),
+        bad_landing: asset_server.load("sounds/bad-landing.wav"),
+        game_over: asset_server.load("sounds/game-over.wav"),
+    });
+}
+
+fn wind_system(
+    wind: Res<Wind>,
+    sinks: Res<Assets<AudioSink>>,
+    mixer: Res<Mixer>,
+    state: Res<State<GameState>>,
+    player: Query<&PlayerComponent>,
+) {
+    let (Some(sink), Ok(player)) = (sinks.get(&wind.0), player.get_single()) else {
+        return;
+    };
+
+    if *state.current() != GameState::Playing {
+        sink.set_volume(0.);
+        return;
+    }
+
+    let (slow, fast) = WIND_SPEED_RANGE;
+    let t = ((player.velocity.length() - slow) / (fast - slow)).clamp(0., 1.);
+
+    // A little wind is always there, and it gets higher as well as louder
+    sink.set_volume(mixer.sfx_volume() * (0.15 + 0.85 * t));
+    sink.set_speed(0.8 + 0.5 * t);
+}
+
+fn play_sfx(audio: &Audio, sound: &Handle<AudioSource>, mixer: &Mixer, volume: f32) {
+    audio.play_with_settings(
+        sound.clone(),
+        PlaybackSettings::ONCE.with_volume(mixer.sfx_volume() * volume),
+    );
+}
+
+fn landing_sounds(
+    mut landing_events: EventReader<LandingEvent>,
+    audio: Res<Audio>,
+    sounds: Res<Sounds>,
+    mixer: Res<Mixer>,
+) {
+    for event in landing_events.iter() {
+        match event.quality {
+            LandingQuality::Perfect => play_sfx(&audio, &sounds.perfect_slide, &mixer, 0.8),
+            // Harder landings thump louder
+            LandingQuality::Normal => play_sfx(
+                &audio,
+                &sounds.land,
+                &mixer,
+                (event.impact / 250.).clamp(0.3, 1.),
+            ),
+            LandingQuality::Bad => play_sfx(&audio, &sounds.bad_landing, &mixer, 1.),
+        }
+    }
+}
+
+fn game_over_sound(audio: Res<Audio>, sounds: Res<Sounds>, mixer: Res<Mixer>) {
+    play_sfx(&audio, &sounds.game_over, &mixer, 1.);
+}
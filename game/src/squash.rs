@@ -0,0 +1,79 @@
+use bevy::prelude::*;
+
+use crate::{
+    player::{GroundContactEvent, PlayerComponent},
+    skin::PlayerVisual,
+    GameSystems,
+};
+
+/// Squashes the bird when it lands and stretches it when it flies fast.
+/// Only the sprite is scaled, which is a child of the player, so the physics are not affected
+pub struct SquashPlugin;
+
+impl Plugin for SquashPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_system(add_squash);
+        app.add_system(squash_system.after(GameSystems::PlayerMovement));
+    }
+}
+
+/// Spring pulling the squash back to rest after a landing or launch
+#[derive(Component, Default)]
+pub struct Squash {
+    /// Positive is squashed flat, negative is stretched tall
+    amount: f32,
+    velocity: f32,
+}
+
+const STIFFNESS: f32 = 300.;
+const DAMPING: f32 = 14.;
+
+fn add_squash(mut commands: Commands, players: Query<Entity, Added<PlayerComponent>>) {
+    for player in players.iter() {
+        commands.entity(player).insert(Squash::default());
+    }
+}
+
+fn squash_system(
+    mut players: Query<(&PlayerComponent, &mut Squash, &Children)>,
+    mut visuals: Query<&mut Transform, With<PlayerVisual>>,
+    mut contact_events: EventReader<GroundContactEvent>,
+    time: Res<Time>,
+) {
+    let Ok((player, mut squash, children)) = players.get_single_mut() else {
+        return;
+    };
+
+    for event in contact_events.iter() {
+        match event {
+            // Harder landings squash more
+            GroundContactEvent::Landed(touchdown) => {
+                squash.velocity += (touchdown.impact / 60.).min(8.);
+            }
+            // Pop up a little when leaving a hill
+            GroundContactEvent::Launched { velocity } => {
+                squash.velocity -= (velocity.y / 200.).clamp(0., 3.);
+            }
+        }
+    }
+
+    let dt = time.delta_seconds().min(1. / 30.);
+    let acceleration = -STIFFNESS * squash.amount - DAMPING * squash.velocity;
+    squash.velocity += acceleration * dt;
+    squash.amount = (squash.amount + squash.velocity * dt).clamp(-0.4, 0.5);
+
+    // The player is rotated along the velocity, so the local x axis points the way it is flying
+    let stretch = ((player.velocity.length() - 400.) / 1200.).clamp(0., 0.3);
+
+    let scale = Vec3::new(
+        (1. + stretch) * (1. + squash.amount * 0.5),
+        (1. - squash.amount) / (1. + stretch),
+        1.,
+    );
+
+    for &child in children.iter() {
+        if let Ok(mut transform) = visuals.get_mut(child) {
+            transform.scale = scale;
+        }
+    }
+}
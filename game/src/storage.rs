@@ -0,0 +1,95 @@
+//! Small key-value store for data which should survive a restart of the game.
+//! Values are stored as RON in the platform data directory, or in localStorage on the web
+
+use bevy::prelude::*;
+use serde::{de::DeserializeOwned, Serialize};
+
+pub fn load<T: DeserializeOwned>(name: &str) -> Option<T> {
+    let contents = read(name)?;
+
+    match ron::from_str(&contents) {
+        Ok(value) => Some(value),
+        Err(err) => {
+            warn!("ignoring unreadable {}: {}", name, err);
+            None
+        }
+    }
+}
+
+pub fn save<T: Serialize>(name: &str, value: &T) {
+    let contents = match ron::ser::to_string_pretty(value, ron::ser::PrettyConfig::default()) {
+        Ok(contents) => contents,
+        Err(err) => {
+            error!("failed to serialize {}: {}", name, err);
+            return;
+        }
+    };
+
+    if let Err(err) = write(name, &contents) {
+        error!("failed to save {}: {}", name, err);
+    }
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+fn path(name: &str) -> Option<std::path::PathBuf> {
+    // iOS has no data dir, but the app sandbox is its home directory
+    let data_dir =
+        dirs::data_dir().or_else(|| dirs::home_dir().map(|home| home.join("Library")))?;
+
+    Some(data_dir.join("bevy_bird").join(format!("{}.ron", name)))
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+pub fn read(name: &str) -> Option<String> {
+    std::fs::read_to_string(path(name)?).ok()
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+pub fn write(name: &str, contents: &str) -> anyhow::Result<()> {
+    let path = path(name).ok_or_else(|| anyhow::anyhow!("no data directory"))?;
+
+    if let Some(dir) = path.parent() {
+        std::fs::create_dir_all(dir)?;
+    }
+    std::fs::write(path, contents)?;
+
+    Ok(())
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+pub fn remove(name: &str) -> anyhow::Result<()> {
+    let path = path(name).ok_or_else(|| anyhow::anyhow!("no data directory"))?;
+    std::fs::remove_file(path)?;
+
+    Ok(())
+}
+
+#[cfg(target_arch = "wasm32")]
+fn local_storage() -> Option<web_sys::Storage> {
+    web_sys::window()?.local_storage().ok()?
+}
+
+#[cfg(target_arch = "wasm32")]
+pub fn read(name: &str) -> Option<String> {
+    local_storage()?
+        .get_item(&format!("bevy_bird.{}", name))
+        .ok()?
+}
+
+#[cfg(target_arch = "wasm32")]
+pub fn write(name: &str, contents: &str) -> anyhow::Result<()> {
+    let storage = local_storage().ok_or_else(|| anyhow::anyhow!("localStorage is unavailable"))?;
+
+    storage
+        .set_item(&format!("bevy_bird.{}", name), contents)
+        .map_err(|err| anyhow::anyhow!("{:?}", err))
+}
+
+#[cfg(target_arch = "wasm32")]
+pub fn remove(name: &str) -> anyhow::Result<()> {
+    let storage = local_storage().ok_or_else(|| anyhow::anyhow!("localStorage is unavailable"))?;
+
+    storage
+        .remove_item(&format!("bevy_bird.{}", name))
+        .map_err(|err| anyhow::anyhow!("{:?}", err))
+}
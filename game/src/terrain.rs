@@ -0,0 +1,105 @@
+//! Shape of the hills, generated from a seed.
+//! The world is split into chunks, each one a valley and a hill, whose height differs from chunk to chunk
+
+use bevy::prelude::*;
+
+use crate::hills::hills_height;
+
+/// Height of the hills the game was first tuned with, relative to their width
+const BASELINE_AMPLITUDE: f32 = 0.1;
+
+/// Range of the height of the hills, relative to their width.
+/// Kept close to [`BASELINE_AMPLITUDE`], so every seed flies like the original hills
+const AMPLITUDE_RANGE: (f32, f32) = (0.08, 0.12);
+
+/// The terrain currently played on. The same seed always gives the same hills
+#[derive(Debug, Clone, Copy)]
+pub struct Terrain {
+    pub seed: u32,
+    /// Width of a chunk in world units, see [`crate::tuning::GameTuning::hill_scale`].
+    /// Chunk `i` is centered at `i * chunk_width`
+    pub chunk_width: f32,
+}
+
+impl Terrain {
+    pub fn new(seed: u32, chunk_width: f32) -> Self {
+        Self { seed, chunk_width }
+    }
+
+    pub fn chunk_at(&self, x: f32) -> i64 {
+        (x / self.chunk_width).round() as i64
+    }
+
+    /// Height of the hill in `chunk`, the amplitude of its sine wave
+    pub fn amplitude(&self, chunk: i64) -> f32 {
+        let hash = splitmix64(((self.seed as u64) << 32) ^ chunk as u64);
+        let t = (hash >> 40) as f32 / (1u64 << 24) as f32;
+
+        let (low, high) = AMPLITUDE_RANGE;
+        low + (high - low) * t
+    }
+
+    /// Amplitudes of the hill at the start and end of `chunk`, see [`hills_height`]
+    pub fn chunk_amplitudes(&self, chunk: i64) -> (f32, f32) {
+        (self.amplitude(chunk), self.amplitude(chunk + 1))
+    }
+
+    /// Position of `x` within its chunk, from 0 at the left edge to 1 at the right one
+    fn chunk_position(&self, x: f32) -> (i64, f32) {
+        let chunk = self.chunk_at(x);
+        (
+            chunk,
+            (x - chunk as f32 * self.chunk_width) / self.chunk_width + 0.5,
+        )
+    }
+
+    /// World y coordinate of the ground at `x`
+    pub fn height(&self, x: f32) -> f32 {
+        let (chunk, u) = self.chunk_position(x);
+        let (start, end) = self.chunk_amplitudes(chunk);
+
+        (hills_height(u, start, end) - 1.) * self.chunk_width
+    }
+
+    /// Normal of the ground at `x`
+    pub fn normal(&self, x: f32) -> Vec2 {
+        let (chunk, u) = self.chunk_position(x);
+        let (start, end) = self.chunk_amplitudes(chunk);
+
+        // Derivative of `hills_height`. The chunk is scaled the same way horizontally and vertically,
+        // so this is the slope in the world as well
+        let amplitude = start + (end - start) * smoothstep(u);
+        let amplitude_slope = (end - start) * 6. * u * (1. - u);
+        let wave = u * std::f32::consts::TAU;
+        let slope = amplitude_slope * wave.sin() + amplitude * std::f32::consts::TAU * wave.cos();
+
+        // The slides are tuned for a ground steeper than the hills look,
+        // as steep as a wave of unit slope on hills of the baseline height
+        let slope = slope / (BASELINE_AMPLITUDE * std::f32::consts::TAU);
+
+        Vec2::new(-slope, 1.).normalize()
+    }
+}
+
+pub fn smoothstep(t: f32) -> f32 {
+    t * t * (3. - 2. * t)
+}
+
+fn splitmix64(mut x: u64) -> u64 {
+    x = x.wrapping_add(0x9e3779b97f4a7c15);
+    x = (x ^ (x >> 30)).wrapping_mul(0xbf58476d1ce4e5b9);
+    x = (x ^ (x >> 27)).wrapping_mul(0x94d049bb133111eb);
+    x ^ (x >> 31)
+}
+
+/// A seed for new, random hills
+pub fn random_seed() -> u32 {
+    let time = crate::clock::unix_time();
+    splitmix64((time * 1000.) as u64) as u32
+}
+
+/// The seed of the daily islands for `date`, the same for every player on that day
+pub fn daily_seed(date: &str) -> u32 {
+    date.bytes()
+        .fold(0, |hash, byte| splitmix64(hash ^ byte as u64)) as u32
+}
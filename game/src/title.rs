@@ -0,0 +1,140 @@
+use bevy::prelude::*;
+
+use crate::{
+    autopilot::Autopilot,
+    clock,
+    input::{Action, ActionState, InputMap},
+    pause::overlay_node,
+    replay::ReplayPlayback,
+    save::SaveData,
+    terrain::{self, Terrain},
+    GameConfig, GameState, RestartEvent,
+};
+
+/// The title screen, where a run over new islands or over the daily islands is started
+pub struct TitlePlugin;
+
+impl Plugin for TitlePlugin {
+    fn build(&self, app: &mut App) {
+        app.insert_resource(GameMode::FreeFlight);
+        app.add_system_set(
+            SystemSet::on_enter(GameState::Title)
+                .with_system(spawn_title_screen)
+                .with_system(stop_playback),
+        );
+        app.add_system_set(SystemSet::on_update(GameState::Title).with_system(title_input));
+        app.add_system_set(SystemSet::on_exit(GameState::Title).with_system(despawn_title_screen));
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum GameMode {
+    /// Islands from a random seed
+    FreeFlight,
+    /// The islands of the day, the same for every player
+    Daily { date: String },
+}
+
+#[derive(Component)]
+struct TitleScreen;
+
+fn spawn_title_screen(
+    mut commands: Commands,
+    asset_server: Res<AssetServer>,
+    save: Res<SaveData>,
+    input_map: Res<InputMap>,
+) {
+    let font = asset_server.load("fonts/DejaVuSans.ttf");
+
+    let text_style = |font_size: f32| TextStyle {
+        font: font.clone(),
+        font_size,
+        color: Color::WHITE,
+    };
+
+    let daily_best = match save
+        .leaderboard
+        .ranking(terrain::daily_seed(&clock::today()))
+        .first()
+    {
+        Some(best) => format!("Today's best: {}", best.score),
+        None => "No flights over today's islands yet".to_string(),
+    };
+
+    let lines = [
+        ("Bevy Bird".to_string(), 48.),
+        (
+            format!("{} to fly over new islands", input_map.label(Action::Dive)),
+            20.,
+        ),
+        (
+            format!("{} for the daily islands", input_map.label(Action::Daily)),
+            20.,
+        ),
+        (
+            format!(
+                "{} to watch the autopilot fly",
+                input_map.label(Action::Autopilot)
+            ),
+            20.,
+        ),
+        (daily_best, 16.),
+    ];
+
+    commands
+        .spawn_bundle(overlay_node(0.3))
+        .insert(TitleScreen)
+        .with_children(|screen| {
+            for (line, font_size) in lines {
+                screen.spawn_bundle(TextBundle {
+                    text: Text::with_section(line, text_style(font_size), Default::default()),
+                    style: Style {
+                        margin: Rect::all(Val::Px(8.)),
+                        ..Default::default()
+                    },
+                    ..Default::default()
+                });
+            }
+        });
+}
+
+fn title_input(
+    mut commands: Commands,
+    mut actions: ResMut<ActionState>,
+    mut state: ResMut<State<GameState>>,
+    mut mode: ResMut<GameMode>,
+    mut restart_events: EventWriter<RestartEvent>,
+    mut terrain: ResMut<Terrain>,
+    config: Res<GameConfig>,
+) {
+    if actions.consume(Action::Daily) {
+        let date = clock::today();
+        terrain.seed = terrain::daily_seed(&date);
+        *mode = GameMode::Daily { date };
+    } else if actions.consume(Action::Autopilot) {
+        terrain.seed = terrain::random_seed();
+        *mode = GameMode::FreeFlight;
+        commands.insert_resource(Autopilot);
+    } else if actions.consume(Action::Dive) {
+        terrain.seed = config.seed.unwrap_or_else(terrain::random_seed);
+        *mode = GameMode::FreeFlight;
+    } else {
+        return;
+    }
+
+    // Brings up the hills of the new seed
+    restart_events.send(RestartEvent);
+    let _ = state.set(GameState::Playing);
+}
+
+// Back on the title screen after watching a replay or the autopilot, the game is played again
+fn stop_playback(mut commands: Commands) {
+    commands.remove_resource::<ReplayPlayback>();
+    commands.remove_resource::<Autopilot>();
+}
+
+fn despawn_title_screen(mut commands: Commands, screens: Query<Entity, With<TitleScreen>>) {
+    for screen in screens.iter() {
+        commands.entity(screen).despawn_recursive();
+    }
+}
@@ -0,0 +1,462 @@
+use std::collections::VecDeque;
+
+use bevy::{
+    ecs::system::lifetimeless::SRes,
+    prelude::*,
+    reflect::TypeUuid,
+    render::{
+        mesh::Indices,
+        render_asset::{PrepareAssetError, RenderAsset},
+        render_resource::{
+            std140::{AsStd140, Std140},
+            BindGroup, BindGroupDescriptor, BindGroupEntry, BindGroupLayoutDescriptor,
+            BindGroupLayoutEntry, BindingType, Buffer, BufferBindingType, BufferInitDescriptor,
+            BufferSize, BufferUsages, PrimitiveTopology, RenderPipelineDescriptor, ShaderStages,
+            VertexAttribute, VertexBufferLayout, VertexFormat, VertexStepMode,
+        },
+        renderer::RenderDevice,
+    },
+    sprite::{
+        Material2dPipeline, Material2dPlugin, MaterialMesh2dBundle, Mesh2dHandle,
+        SpecializedMaterial2d,
+    },
+    utils::HashSet,
+};
+
+use crate::{GameState, GameSystems, GameTime, RestartEvent};
+
+/// Renders a ribbon behind every entity with a [`Trail`] component,
+/// following the path the entity has taken during the last few frames.
+/// The ribbon is a child of the entity, so it goes away when the entity is despawned
+pub struct TrailPlugin;
+
+impl Plugin for TrailPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_plugin(Material2dPlugin::<TrailMaterial>::default());
+        app.init_resource::<TrailClock>();
+        app.add_system(advance_trail_clock.before(GameSystems::Trail));
+        app.add_system(spawn_trail_ribbons.before(GameSystems::Trail));
+        app.add_system(
+            trail_system
+                .label(GameSystems::Trail)
+                .after(GameSystems::PlayerMovement),
+        );
+        // Removals by the commands of the update stage are only seen by the stages after it
+        app.add_system_to_stage(CoreStage::PostUpdate, despawn_trail_ribbons);
+        app.add_system(clear_trails_on_restart.before(GameSystems::Trail));
+    }
+}
+
+#[derive(Component, Clone)]
+pub struct Trail {
+    /// Number of segments the ribbon is made of
+    pub segments: usize,
+    /// Time in seconds between two neighbouring segments
+    pub spacing: f32,
+    /// How much of the opacity the end of the ribbon has lost, from 0 to 1
+    pub fade: f32,
+    /// Width of the ribbon right behind the entity, it tapers to a point at the end
+    pub width: f32,
+    pub color: Color,
+}
+
+impl Default for Trail {
+    fn default() -> Self {
+        Self {
+            segments: 24,
+            spacing: 0.008,
+            fade: 1.,
+            width: 30.,
+            color: Color::rgba(1., 1., 1., 0.8),
+        }
+    }
+}
+
+/// Seconds of play the trails are recorded and aged on. It stands still unless the game is playing,
+/// so a paused trail keeps its shape instead of shrinking into its owner
+#[derive(Default)]
+struct TrailClock(f64);
+
+/// The ribbon mesh rendering the trail of `owner`, its parent
+#[derive(Component)]
+pub struct TrailRibbon {
+    pub owner: Entity,
+}
+
+#[derive(Clone, Copy)]
+struct PoseSample {
+    time: f64,
+    translation: Vec3,
+    rotation: Quat,
+}
+
+/// Ring buffer of the poses of a trail owner from the last few frames,
+/// used to place the trail at fixed time offsets independent of the frame rate
+#[derive(Component, Default)]
+pub struct PoseHistory {
+    samples: VecDeque<PoseSample>,
+}
+
+impl PoseHistory {
+    /// Start a new history at the current pose, so the trail grows from there rather than from an old position
+    fn starting_at(time: f64, transform: &Transform) -> Self {
+        let mut history = Self::default();
+        history.record(time, transform, 0.);
+        history
+    }
+
+    fn record(&mut self, time: f64, transform: &Transform, max_age: f64) {
+        self.samples.push_front(PoseSample {
+            time,
+            translation: transform.translation,
+            rotation: transform.rotation,
+        });
+
+        // Keep a single sample older than `max_age` around, so the last segment can still interpolate
+        while self.samples.len() > 2 && self.samples[self.samples.len() - 2].time < time - max_age {
+            self.samples.pop_back();
+        }
+    }
+
+    /// Interpolated pose at `time`, clamped to the oldest recorded sample
+    fn sample(&self, time: f64) -> Option<(Vec3, Quat)> {
+        let mut newer = self.samples.front()?;
+
+        for older in self.samples.iter() {
+            if older.time <= time {
+                if newer.time <= older.time {
+                    return Some((older.translation, older.rotation));
+                }
+
+                let t = ((time - older.time) / (newer.time - older.time)) as f32;
+                return Some((
+                    older.translation.lerp(newer.translation, t),
+                    older.rotation.slerp(newer.rotation, t),
+                ));
+            }
+            newer = older;
+        }
+
+        Some((newer.translation, newer.rotation))
+    }
+
+    fn len(&self) -> usize {
+        self.samples.len()
+    }
+
+    fn clear(&mut self) {
+        self.samples.clear();
+    }
+}
+
+/// Build a ribbon along `points`, tapering from `width` at the first point to nothing at the last.
+/// The uv x coordinate goes from 0 to 1 along the ribbon, and y from 0 to 1 across it
+fn ribbon_mesh(points: &[(Vec3, Quat)], width: f32) -> Mesh {
+    let mut v_pos = vec![];
+    let mut v_uv = vec![];
+
+    let last = points.len().max(2) - 1;
+
+    for (i, &(translation, rotation)) in points.iter().enumerate() {
+        let prev = points[i.saturating_sub(1)].0;
+        let next = points[(i + 1).min(points.len() - 1)].0;
+
+        // Follow the path, but fall back to the heading of the entity when it is standing still
+        let tangent = (prev - next).truncate();
+        let tangent = if tangent.length_squared() > f32::EPSILON {
+            tangent.normalize()
+        } else {
+            (rotation * Vec3::X).truncate()
+        };
+        let normal = tangent.perp();
+
+        let t = i as f32 / last as f32;
+        let half_width = width / 2. * (1. - t);
+
+        let center = translation.truncate();
+        let left = center + normal * half_width;
+        let right = center - normal * half_width;
+
+        v_pos.push([left.x, left.y]);
+        v_pos.push([right.x, right.y]);
+        v_uv.push([t, 0.]);
+        v_uv.push([t, 1.]);
+    }
+
+    // Two triangles between every pair of neighbouring points
+    let mut indices = vec![];
+    for i in 0..(points.len().saturating_sub(1) as u32) {
+        let x = i * 2;
+        indices.extend_from_slice(&[x, x + 1, x + 3]);
+        indices.extend_from_slice(&[x, x + 3, x + 2]);
+    }
+
+    let mut mesh = Mesh::new(PrimitiveTopology::TriangleList);
+    mesh.set_attribute(Mesh::ATTRIBUTE_POSITION, v_pos);
+    mesh.set_attribute(Mesh::ATTRIBUTE_UV_0, v_uv);
+    mesh.set_indices(Some(Indices::U32(indices)));
+
+    mesh
+}
+
+// Spawn a ribbon when a trail is added, or update its material when the settings change
+fn spawn_trail_ribbons(
+    mut commands: Commands,
+    owners: Query<(Entity, &Trail, &Transform), Changed<Trail>>,
+    ribbons: Query<(&TrailRibbon, &Handle<TrailMaterial>)>,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut materials: ResMut<Assets<TrailMaterial>>,
+    clock: Res<TrailClock>,
+) {
+    for (owner, trail, transform) in owners.iter() {
+        let trail_material = TrailMaterial {
+            color: trail.color,
+            fade: trail.fade,
+        };
+
+        if let Some((_, material)) = ribbons.iter().find(|(ribbon, _)| ribbon.owner == owner) {
+            if let Some(material) = materials.get_mut(material) {
+                *material = trail_material;
+            }
+            continue;
+        }
+
+        commands
+            .entity(owner)
+            .insert(PoseHistory::starting_at(clock.0, transform));
+
+        let start = (Vec3::ZERO, Quat::IDENTITY);
+
+        let ribbon = commands
+            .spawn_bundle(MaterialMesh2dBundle {
+                mesh: meshes.add(ribbon_mesh(&[start, start], 0.)).into(),
+                material: materials.add(trail_material),
+                // Draw the ribbon right behind its owner
+                transform: Transform::from_xyz(0., 0., -0.01),
+                ..Default::default()
+            })
+            .insert(TrailRibbon { owner })
+            .id();
+        commands.entity(owner).add_child(ribbon);
+    }
+}
+
+fn advance_trail_clock(
+    mut clock: ResMut<TrailClock>,
+    game_time: Res<GameTime>,
+    state: Res<State<GameState>>,
+) {
+    if *state.current() == GameState::Playing {
+        clock.0 += game_time.delta_seconds() as f64;
+    }
+}
+
+fn trail_system(
+    mut owners: Query<(&Trail, &Transform, &Visibility, &mut PoseHistory)>,
+    mut ribbons: Query<(&TrailRibbon, &Mesh2dHandle, &mut Visibility), Without<Trail>>,
+    mut meshes: ResMut<Assets<Mesh>>,
+    clock: Res<TrailClock>,
+) {
+    let now = clock.0;
+
+    for (trail, transform, visibility, mut history) in owners.iter_mut() {
+        if visibility.is_visible {
+            let trail_duration = trail.segments as f64 * trail.spacing as f64;
+            history.record(now, transform, trail_duration);
+        } else {
+            history.clear();
+        }
+    }
+
+    for (ribbon, mesh, mut ribbon_visibility) in ribbons.iter_mut() {
+        let Ok((trail, transform, visibility, history)) = owners.get_mut(ribbon.owner) else {
+            continue;
+        };
+
+        ribbon_visibility.is_visible = visibility.is_visible && history.len() > 1;
+        if !ribbon_visibility.is_visible {
+            continue;
+        }
+
+        // The history is in world space, the ribbon moves along with its owner
+        let to_owner = transform.compute_matrix().inverse();
+        let owner_rotation = transform.rotation.inverse();
+
+        let points: Vec<(Vec3, Quat)> = (0..=trail.segments)
+            .filter_map(|i| history.sample(now - i as f64 * trail.spacing as f64))
+            .map(|(translation, rotation)| {
+                (
+                    to_owner.transform_point3(translation),
+                    owner_rotation * rotation,
+                )
+            })
+            .collect();
+
+        if let Some(mesh) = meshes.get_mut(&mesh.0) {
+            *mesh = ribbon_mesh(&points, trail.width);
+        }
+    }
+}
+
+// Don't draw a trail from where the last run ended to the start
+fn clear_trails_on_restart(
+    mut restart_events: EventReader<RestartEvent>,
+    mut histories: Query<&mut PoseHistory>,
+) {
+    if restart_events.iter().count() == 0 {
+        return;
+    }
+
+    for mut history in histories.iter_mut() {
+        history.clear();
+    }
+}
+
+// Clean up the ribbon and history when the trail is removed.
+// A despawned owner takes its ribbon along, as long as it is despawned recursively
+fn despawn_trail_ribbons(
+    mut commands: Commands,
+    removed: RemovedComponents<Trail>,
+    ribbons: Query<(Entity, &TrailRibbon)>,
+) {
+    let removed_owners: HashSet<Entity> = removed.iter().collect();
+
+    if removed_owners.is_empty() {
+        return;
+    }
+
+    for &owner in removed_owners.iter() {
+        commands.entity(owner).remove::<PoseHistory>();
+    }
+
+    for (ribbon_entity, ribbon) in ribbons.iter() {
+        if removed_owners.contains(&ribbon.owner) {
+            commands.entity(ribbon_entity).despawn_recursive();
+        }
+    }
+}
+
+/// Material for the trail ribbon, fading out along the length of the ribbon and towards its edges
+#[derive(Debug, Clone, TypeUuid)]
+#[uuid = "5c8e3b7d-3d2a-4b8e-9f5a-2b6c1f0e7a41"]
+pub struct TrailMaterial {
+    pub color: Color,
+    pub fade: f32,
+}
+
+#[derive(Clone, AsStd140)]
+struct TrailMaterialUniformData {
+    color: Vec4,
+    fade: f32,
+}
+
+pub struct GpuTrailMaterial {
+    _buffer: Buffer,
+    bind_group: BindGroup,
+}
+
+impl RenderAsset for TrailMaterial {
+    type ExtractedAsset = TrailMaterial;
+
+    type PreparedAsset = GpuTrailMaterial;
+
+    type Param = (SRes<RenderDevice>, SRes<Material2dPipeline<TrailMaterial>>);
+
+    fn extract_asset(&self) -> Self::ExtractedAsset {
+        self.clone()
+    }
+
+    fn prepare_asset(
+        material: Self::ExtractedAsset,
+        (render_device, trail_pipeline): &mut bevy::ecs::system::SystemParamItem<Self::Param>,
+    ) -> Result<Self::PreparedAsset, PrepareAssetError<Self::ExtractedAsset>> {
+        let value = TrailMaterialUniformData {
+            color: material.color.as_linear_rgba_f32().into(),
+            fade: material.fade,
+        };
+
+        let buffer = render_device.create_buffer_with_data(&BufferInitDescriptor {
+            label: Some("trail_material_uniform_buffer"),
+            usage: BufferUsages::UNIFORM | BufferUsages::COPY_DST,
+            contents: value.as_std140().as_bytes(),
+        });
+
+        let bind_group = render_device.create_bind_group(&BindGroupDescriptor {
+            entries: &[BindGroupEntry {
+                binding: 0,
+                resource: buffer.as_entire_binding(),
+            }],
+            label: Some("trail_material_bind_group"),
+            layout: &trail_pipeline.material2d_layout,
+        });
+
+        Ok(GpuTrailMaterial {
+            _buffer: buffer,
+            bind_group,
+        })
+    }
+}
+
+// Like the hills, the ribbon uses its own vertex buffer layout, with a 2d position and an uv
+impl SpecializedMaterial2d for TrailMaterial {
+    fn bind_group(
+        render_asset: &<Self as bevy::render::render_asset::RenderAsset>::PreparedAsset,
+    ) -> &bevy::render::render_resource::BindGroup {
+        &render_asset.bind_group
+    }
+
+    fn bind_group_layout(
+        render_device: &bevy::render::renderer::RenderDevice,
+    ) -> bevy::render::render_resource::BindGroupLayout {
+        render_device.create_bind_group_layout(&BindGroupLayoutDescriptor {
+            label: Some("trail_material_layout"),
+            entries: &[BindGroupLayoutEntry {
+                binding: 0,
+                visibility: ShaderStages::FRAGMENT,
+                ty: BindingType::Buffer {
+                    ty: BufferBindingType::Uniform,
+                    has_dynamic_offset: false,
+                    min_binding_size: BufferSize::new(
+                        TrailMaterialUniformData::std140_size_static() as u64,
+                    ),
+                },
+                count: None,
+            }],
+        })
+    }
+
+    fn vertex_shader(asset_server: &AssetServer) -> Option<Handle<Shader>> {
+        Some(asset_server.load("shaders/trail.wgsl"))
+    }
+
+    fn fragment_shader(asset_server: &AssetServer) -> Option<Handle<Shader>> {
+        Some(asset_server.load("shaders/trail.wgsl"))
+    }
+
+    type Key = ();
+    fn key(_material: &<Self as RenderAsset>::PreparedAsset) -> Self::Key {}
+
+    fn specialize(_key: Self::Key, descriptor: &mut RenderPipelineDescriptor) {
+        // Mesh attributes are interleaved sorted by name, so the position comes before the uv
+        let vertex_attributes = vec![
+            VertexAttribute {
+                format: VertexFormat::Float32x2,
+                offset: 0,
+                shader_location: 0,
+            },
+            VertexAttribute {
+                format: VertexFormat::Float32x2,
+                offset: 8,
+                shader_location: 1,
+            },
+        ];
+
+        let vertex_array_stride = 16;
+
+        descriptor.vertex.buffers = vec![VertexBufferLayout {
+            array_stride: vertex_array_stride,
+            step_mode: VertexStepMode::Vertex,
+            attributes: vertex_attributes,
+        }];
+    }
+}
@@ -0,0 +1,152 @@
+use std::path::Path;
+
+use bevy::{
+    asset::{AssetLoader, LoadContext, LoadedAsset},
+    prelude::*,
+    reflect::TypeUuid,
+    utils::BoxedFuture,
+};
+use serde::{Deserialize, Serialize};
+
+use crate::{terrain::Terrain, RestartEvent};
+
+/// Constants for the feel of the game, loaded from `assets/game.tuning.ron`.
+/// The file is reloaded when it changes, so the values can be tweaked while playing
+pub struct TuningPlugin;
+
+impl Plugin for TuningPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_asset::<GameTuning>();
+        app.init_asset_loader::<GameTuningLoader>();
+        // Used until the file is loaded
+        app.init_resource::<GameTuning>();
+        app.add_startup_system(load_tuning);
+        app.add_system_to_stage(CoreStage::PreUpdate, apply_tuning);
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, TypeUuid)]
+#[uuid = "5d3f1c2a-8b7e-4e0f-9a61-2c4d7b9e0f13"]
+pub struct GameTuning {
+    /// Downward acceleration while gliding
+    pub gravity: f32,
+    /// Downward acceleration while diving
+    pub dive_gravity: f32,
+    /// The bird never slides along the ground slower than this to the right
+    pub min_ground_speed: f32,
+    /// Height above which the bird is slowed down while climbing
+    pub ceiling: f32,
+    /// Downward acceleration above the ceiling
+    pub ceiling_drag: f32,
+    /// Velocity of the bird at the start of a run
+    pub initial_velocity: (f32, f32),
+    pub camera: CameraTuning,
+    /// Width and height of a hill, in world units
+    pub hill_scale: f32,
+}
+
+/// The camera zooms out the faster the bird flies to the right:
+/// `scale = ((velocity.x + speed_offset) / zoom_speed + base_scale).min(max_scale)`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CameraTuning {
+    pub speed_offset: f32,
+    pub zoom_speed: f32,
+    pub base_scale: f32,
+    pub max_scale: f32,
+}
+
+impl Default for GameTuning {
+    fn default() -> Self {
+        Self {
+            gravity: 100.,
+            dive_gravity: 400.,
+            min_ground_speed: 80.,
+            ceiling: 360.,
+            ceiling_drag: 300.,
+            initial_velocity: (200., 0.),
+            camera: CameraTuning {
+                speed_offset: 80.,
+                zoom_speed: 400.,
+                base_scale: 0.2,
+                max_scale: 1.6,
+            },
+            hill_scale: 256.,
+        }
+    }
+}
+
+impl GameTuning {
+    pub fn initial_velocity(&self) -> Vec2 {
+        Vec2::new(self.initial_velocity.0, self.initial_velocity.1)
+    }
+
+    /// Reads the tuning from a file, for running the game logic without the asset server
+    pub fn from_file(path: &Path) -> anyhow::Result<Self> {
+        let contents = std::fs::read_to_string(path)?;
+
+        Ok(ron::from_str(&contents)?)
+    }
+}
+
+impl CameraTuning {
+    /// Scale of the camera when the bird flies with `velocity`
+    pub fn scale(&self, velocity: Vec2) -> f32 {
+        ((velocity.x + self.speed_offset) / self.zoom_speed + self.base_scale).min(self.max_scale)
+    }
+}
+
+#[derive(Default)]
+pub struct GameTuningLoader;
+
+impl AssetLoader for GameTuningLoader {
+    fn load<'a>(
+        &'a self,
+        bytes: &'a [u8],
+        load_context: &'a mut LoadContext,
+    ) -> BoxedFuture<'a, Result<(), anyhow::Error>> {
+        Box::pin(async move {
+            let tuning: GameTuning = ron::de::from_bytes(bytes)?;
+            load_context.set_default_asset(LoadedAsset::new(tuning));
+            Ok(())
+        })
+    }
+
+    // Like the skins file, named so other RON assets aren't taken for tuning
+    fn extensions(&self) -> &[&str] {
+        &["tuning.ron"]
+    }
+}
+
+struct TuningFile(Handle<GameTuning>);
+
+fn load_tuning(mut commands: Commands, asset_server: Res<AssetServer>) {
+    commands.insert_resource(TuningFile(asset_server.load("game.tuning.ron")));
+}
+
+// Copies the loaded file into the `GameTuning` resource, which the game reads its constants from
+fn apply_tuning(
+    file: Res<TuningFile>,
+    files: Res<Assets<GameTuning>>,
+    mut tuning_events: EventReader<AssetEvent<GameTuning>>,
+    mut tuning: ResMut<GameTuning>,
+    mut terrain: ResMut<Terrain>,
+    mut restart_events: EventWriter<RestartEvent>,
+) {
+    for event in tuning_events.iter() {
+        let (AssetEvent::Created { handle } | AssetEvent::Modified { handle }) = event else {
+            continue;
+        };
+        let Some(loaded) = files.get(handle).filter(|_| *handle == file.0) else {
+            continue;
+        };
+
+        info!("applying game tuning");
+        *tuning = loaded.clone();
+
+        // The hills have to be built again at their new size, which the bird can't fly on mid-run
+        if terrain.chunk_width != tuning.hill_scale {
+            terrain.chunk_width = tuning.hill_scale;
+            restart_events.send(RestartEvent);
+        }
+    }
+}
@@ -0,0 +1,44 @@
+//! Assembles an app from the plugins of the game's parts, without `BevyBirdPlugin`,
+//! the way an embedder picking its own plugins would
+
+use std::time::Duration;
+
+use bevy::{asset::AssetPlugin, input::InputPlugin, prelude::*, window::WindowPlugin};
+use bevy_bird_lib::{player::PlayerComponent, CorePlugin, GameState, PlayerPlugin};
+
+/// A windowless app with the bird and what it relies on
+fn headless_app() -> App {
+    let mut app = App::new();
+    app.add_plugins(MinimalPlugins)
+        .add_plugin(WindowPlugin::default())
+        .add_plugin(InputPlugin)
+        .add_plugin(AssetPlugin)
+        // Added by Bevy's render plugins, which need a window
+        .add_asset::<Mesh>()
+        .add_asset::<Image>()
+        .add_asset::<TextureAtlas>()
+        .add_plugin(CorePlugin {
+            initial_state: GameState::Playing,
+            seed: 1,
+        })
+        .add_plugin(PlayerPlugin);
+    app
+}
+
+#[test]
+fn bird_flies_without_the_whole_game() {
+    let mut app = headless_app();
+
+    // The physics only steps once a frame's worth of time has passed
+    for _ in 0..20 {
+        app.update();
+        std::thread::sleep(Duration::from_millis(10));
+    }
+
+    let mut players = app.world.query::<(&Transform, &PlayerComponent)>();
+    let (transform, _) = players
+        .iter(&app.world)
+        .next()
+        .expect("the player should have been spawned");
+    assert!(transform.translation.x > 0., "the bird didn't fly ahead");
+}
@@ -0,0 +1,150 @@
+use bevy::{
+    ecs::event::Events,
+    input::{gamepad::GamepadEventRaw, InputPlugin},
+    prelude::*,
+    window::WindowPlugin,
+};
+use bevy_bird_lib::{
+    input::{Action, ActionState, InputMap, InputMapPlugin},
+    GameState,
+};
+
+const GAMEPAD: Gamepad = Gamepad(0);
+
+/// A windowless app with just the input handling of the game
+fn headless_app() -> App {
+    let mut app = App::new();
+    app.add_plugins(MinimalPlugins)
+        .add_plugin(WindowPlugin::default())
+        .add_plugin(InputPlugin)
+        .add_state(GameState::Playing)
+        .add_plugin(InputMapPlugin)
+        // Don't depend on bindings saved by a previous run of the game
+        .insert_resource(InputMap::default());
+    app
+}
+
+// Sent the same way gilrs would, so Bevy turns them into `GamepadEvent`s and button input
+fn send_gamepad_event(app: &mut App, gamepad: Gamepad, event: GamepadEventType) {
+    app.world
+        .get_resource_mut::<Events<GamepadEventRaw>>()
+        .unwrap()
+        .send(GamepadEventRaw(gamepad, event));
+}
+
+fn connected_app() -> App {
+    let mut app = headless_app();
+    send_gamepad_event(&mut app, GAMEPAD, GamepadEventType::Connected);
+    app.update();
+    app
+}
+
+fn pressed(app: &App, action: Action) -> bool {
+    app.world
+        .get_resource::<ActionState>()
+        .unwrap()
+        .pressed(action)
+}
+
+#[test]
+fn face_button_dives() {
+    let mut app = connected_app();
+
+    send_gamepad_event(
+        &mut app,
+        GAMEPAD,
+        GamepadEventType::ButtonChanged(GamepadButtonType::South, 1.),
+    );
+    app.update();
+    assert!(pressed(&app, Action::Dive));
+
+    send_gamepad_event(
+        &mut app,
+        GAMEPAD,
+        GamepadEventType::ButtonChanged(GamepadButtonType::South, 0.),
+    );
+    app.update();
+    assert!(!pressed(&app, Action::Dive));
+}
+
+#[test]
+fn trigger_dives_once_pulled_far_enough() {
+    let mut app = connected_app();
+
+    send_gamepad_event(
+        &mut app,
+        GAMEPAD,
+        GamepadEventType::ButtonChanged(GamepadButtonType::RightTrigger2, 0.2),
+    );
+    app.update();
+    assert!(!pressed(&app, Action::Dive));
+
+    send_gamepad_event(
+        &mut app,
+        GAMEPAD,
+        GamepadEventType::ButtonChanged(GamepadButtonType::RightTrigger2, 0.9),
+    );
+    app.update();
+    assert!(pressed(&app, Action::Dive));
+}
+
+#[test]
+fn start_pauses() {
+    let mut app = connected_app();
+
+    send_gamepad_event(
+        &mut app,
+        GAMEPAD,
+        GamepadEventType::ButtonChanged(GamepadButtonType::Start, 1.),
+    );
+    app.update();
+    assert!(app
+        .world
+        .get_resource::<ActionState>()
+        .unwrap()
+        .just_pressed(Action::Pause));
+}
+
+#[test]
+fn disconnect_releases_buttons_and_pauses() {
+    let mut app = connected_app();
+
+    send_gamepad_event(
+        &mut app,
+        GAMEPAD,
+        GamepadEventType::ButtonChanged(GamepadButtonType::South, 1.),
+    );
+    app.update();
+    assert!(pressed(&app, Action::Dive));
+
+    send_gamepad_event(&mut app, GAMEPAD, GamepadEventType::Disconnected);
+    app.update();
+
+    assert!(!pressed(&app, Action::Dive));
+    assert_eq!(
+        app.world
+            .get_resource::<State<GameState>>()
+            .unwrap()
+            .current(),
+        &GameState::Paused
+    );
+}
+
+#[test]
+fn second_gamepad_can_be_plugged_in_later() {
+    let mut app = connected_app();
+
+    let second = Gamepad(1);
+
+    send_gamepad_event(&mut app, second, GamepadEventType::Connected);
+    app.update();
+
+    send_gamepad_event(
+        &mut app,
+        second,
+        GamepadEventType::ButtonChanged(GamepadButtonType::South, 1.),
+    );
+    app.update();
+
+    assert!(pressed(&app, Action::Dive));
+}
@@ -0,0 +1,69 @@
+use bevy::prelude::*;
+use bevy_bird_lib::input::{Action, Binding, InputMap};
+
+fn gamepad_bindings(input_map: &InputMap, action: Action) -> Vec<Binding> {
+    input_map
+        .bindings(action)
+        .iter()
+        .copied()
+        .filter(|binding| matches!(binding, Binding::Gamepad(_)))
+        .collect()
+}
+
+#[test]
+fn rebinding_replaces_only_the_selected_binding() {
+    let mut input_map = InputMap::default();
+    let slot = input_map
+        .bindings(Action::Dive)
+        .iter()
+        .position(|&binding| binding == Binding::Gamepad(GamepadButtonType::LeftTrigger2))
+        .unwrap();
+
+    let conflict = input_map.rebind(
+        Action::Dive,
+        slot,
+        Binding::Gamepad(GamepadButtonType::DPadDown),
+    );
+
+    assert_eq!(conflict, None);
+    assert_eq!(
+        gamepad_bindings(&input_map, Action::Dive),
+        vec![
+            Binding::Gamepad(GamepadButtonType::South),
+            Binding::Gamepad(GamepadButtonType::DPadDown),
+            Binding::Gamepad(GamepadButtonType::RightTrigger2),
+        ]
+    );
+}
+
+#[test]
+fn rebinding_past_the_last_binding_adds_one() {
+    let mut input_map = InputMap::default();
+    let count = input_map.bindings(Action::Restart).len();
+
+    input_map.rebind(Action::Restart, count, Binding::Key(KeyCode::F5));
+
+    let bindings = input_map.bindings(Action::Restart);
+    assert_eq!(bindings.len(), count + 1);
+    assert_eq!(bindings[count], Binding::Key(KeyCode::F5));
+}
+
+#[test]
+fn rebinding_to_a_used_input_swaps_it() {
+    let mut input_map = InputMap::default();
+
+    // R restarts, and replaces the B of toggling the skin
+    let conflict = input_map.rebind(Action::ToggleSkin, 0, Binding::Key(KeyCode::R));
+
+    assert_eq!(conflict, Some(Action::Restart));
+    assert_eq!(
+        input_map.bindings(Action::ToggleSkin)[0],
+        Binding::Key(KeyCode::R)
+    );
+    assert!(input_map
+        .bindings(Action::Restart)
+        .contains(&Binding::Key(KeyCode::B)));
+    assert!(!input_map
+        .bindings(Action::Restart)
+        .contains(&Binding::Key(KeyCode::R)));
+}
@@ -0,0 +1,31 @@
+//! Runs whole days with the dive policies of the simulate binary, checking that the seed
+//! changes how a run goes, so simulating many seeds measures something
+
+use bevy_bird_lib::{
+    autopilot::Autopilot,
+    sim::{DivePolicy, DiveWhileFalling, Glide, RunSummary, Simulation},
+    tuning::GameTuning,
+};
+
+fn summary(seed: u32, mut policy: impl DivePolicy) -> RunSummary {
+    let mut sim = Simulation::new(seed, GameTuning::default());
+    sim.run_until_sunset(&mut policy);
+    sim.summary()
+}
+
+fn assert_seeds_differ(first: RunSummary, second: RunSummary) {
+    assert!(
+        first.score != second.score || first.distance != second.distance,
+        "seeds {} and {} flew the same run: {:?}",
+        first.seed,
+        second.seed,
+        first
+    );
+}
+
+#[test]
+fn seeds_fly_differently() {
+    assert_seeds_differ(summary(1, Glide), summary(2, Glide));
+    assert_seeds_differ(summary(1, DiveWhileFalling), summary(2, DiveWhileFalling));
+    assert_seeds_differ(summary(1, Autopilot), summary(2, Autopilot));
+}
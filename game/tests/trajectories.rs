@@ -0,0 +1,137 @@
+//! Flies the bird with fixed input over fixed terrain, and compares where it went with the
+//! trajectories in `tests/golden`. Run with `UPDATE_GOLDEN=1` to write them again,
+//! after changing the physics on purpose
+
+use std::{fmt::Write, path::PathBuf};
+
+use bevy_bird_lib::{run::DAY_TICKS, sim::Simulation, tuning::GameTuning};
+
+/// Physics steps between the samples of a trajectory, a second of flight
+const SAMPLE_INTERVAL: u64 = 120;
+
+/// How far a sample may be off, relative to its size, so the tests don't fail
+/// on rounding differences between platforms
+const TOLERANCE: f32 = 1e-3;
+
+fn golden_path(name: &str) -> PathBuf {
+    PathBuf::from(env!("CARGO_MANIFEST_DIR"))
+        .join("tests/golden")
+        .join(format!("{}.txt", name))
+}
+
+/// Flies a whole day over the terrain of `seed` with the default tuning,
+/// diving at the ticks `diving` returns true for, and samples the position and velocity
+fn trajectory(seed: u32, diving: impl Fn(u64) -> bool) -> String {
+    let mut sim = Simulation::new(seed, GameTuning::default());
+    let mut samples = "# tick x y velocity_x velocity_y grounded\n".to_string();
+
+    for sample_tick in (SAMPLE_INTERVAL..=DAY_TICKS).step_by(SAMPLE_INTERVAL as usize) {
+        while sim.tick < sample_tick {
+            sim.step(diving(sim.tick + 1));
+        }
+
+        let position = sim.transform.translation;
+        let velocity = sim.player.velocity;
+        writeln!(
+            samples,
+            "{} {:.3} {:.3} {:.3} {:.3} {}",
+            sim.tick, position.x, position.y, velocity.x, velocity.y, sim.player.grounded
+        )
+        .unwrap();
+    }
+
+    let summary = sim.summary();
+    writeln!(
+        samples,
+        "# score {} landings {} perfect slides {} bad landings {}",
+        summary.score, summary.landings, summary.perfect_slides, summary.bad_landings
+    )
+    .unwrap();
+
+    samples
+}
+
+fn samples_match(actual: &str, expected: &str) -> bool {
+    let lines = |samples: &str| {
+        samples
+            .lines()
+            .map(|line| line.split_whitespace().map(str::to_string).collect())
+            .collect::<Vec<Vec<String>>>()
+    };
+    let (actual, expected) = (lines(actual), lines(expected));
+
+    actual.len() == expected.len()
+        && actual.iter().zip(&expected).all(|(actual, expected)| {
+            actual.len() == expected.len()
+                && actual.iter().zip(expected).all(|(actual, expected)| {
+                    match (actual.parse::<f32>(), expected.parse::<f32>()) {
+                        (Ok(actual), Ok(expected)) => {
+                            (actual - expected).abs() <= TOLERANCE * expected.abs().max(1.)
+                        }
+                        _ => actual == expected,
+                    }
+                })
+        })
+}
+
+fn check_golden(name: &str, actual: String) {
+    let path = golden_path(name);
+
+    if std::env::var_os("UPDATE_GOLDEN").is_some() {
+        std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+        std::fs::write(&path, actual).unwrap();
+        return;
+    }
+
+    let expected = std::fs::read_to_string(&path).unwrap_or_else(|err| {
+        panic!(
+            "failed to read {}, run with UPDATE_GOLDEN=1 to write it: {}",
+            path.display(),
+            err
+        )
+    });
+    assert!(
+        samples_match(&actual, &expected),
+        "the trajectory no longer matches {}, run with UPDATE_GOLDEN=1 if the change is intended\n\
+         expected:\n{}\nactual:\n{}",
+        path.display(),
+        expected,
+        actual
+    );
+}
+
+/// Checks the trajectories over two seeds against their golden files,
+/// and that the seeds put different hills under the bird
+fn check_seeds(name: &str, diving: impl Fn(u64) -> bool + Copy) {
+    let (first, second) = (trajectory(1, diving), trajectory(42, diving));
+    assert_ne!(
+        first, second,
+        "{} flies the same over seeds 1 and 42, the seed no longer shapes the hills",
+        name
+    );
+
+    check_golden(&format!("{}-seed-1", name), first);
+    check_golden(&format!("{}-seed-42", name), second);
+}
+
+#[test]
+fn gliding() {
+    check_seeds("glide", |_| false);
+}
+
+#[test]
+fn diving() {
+    check_seeds("dive", |_| true);
+}
+
+#[test]
+fn diving_every_other_half_second() {
+    check_seeds("pulses", |tick: u64| (tick / 60) % 2 == 1);
+}
+
+#[test]
+fn same_input_flies_the_same_way() {
+    let pulses = |tick: u64| (tick / 90) % 3 == 1;
+
+    assert_eq!(trajectory(7, pulses), trajectory(7, pulses));
+}
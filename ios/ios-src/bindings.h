@@ -1 +1,3 @@
 void main_rs(void);
+void app_will_resign_active(void);
+void app_did_become_active(void);
@@ -1,6 +1,22 @@
+#import <UIKit/UIKit.h>
 #import "bindings.h"
 
 int main() {
+    // Winit owns the app delegate, so the game hears about the app moving to the background from these
+    NSNotificationCenter *center = [NSNotificationCenter defaultCenter];
+    [center addObserverForName:UIApplicationWillResignActiveNotification
+                        object:nil
+                         queue:nil
+                    usingBlock:^(NSNotification *notification) {
+                        app_will_resign_active();
+                    }];
+    [center addObserverForName:UIApplicationDidBecomeActiveNotification
+                        object:nil
+                         queue:nil
+                    usingBlock:^(NSNotification *notification) {
+                        app_did_become_active();
+                    }];
+
     main_rs();
     return 0;
 }
@@ -1,7 +1,19 @@
 use bevy::prelude::*;
+use bevy_bird_lib::{AppLifecycle, GameConfig};
 
 // the `bevy_main` proc_macro generates the required ios boilerplate
 #[bevy_main]
 fn main() {
-    bevy_bird_lib::start_game();
+    bevy_bird_lib::start_game(GameConfig::new().fullscreen(true));
+}
+
+// Called from `main.m` when the app stops or starts being the active app, so the game pauses
+#[no_mangle]
+pub extern "C" fn app_will_resign_active() {
+    bevy_bird_lib::app_lifecycle_changed(AppLifecycle::Suspended);
+}
+
+#[no_mangle]
+pub extern "C" fn app_did_become_active() {
+    bevy_bird_lib::app_lifecycle_changed(AppLifecycle::Resumed);
 }
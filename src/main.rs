@@ -1,3 +1,82 @@
+use bevy_bird_lib::GameConfig;
+
 fn main() {
-    bevy_bird_lib::start_game();
+    let config = match parse_args(config(), std::env::args().skip(1)) {
+        Ok(config) => config,
+        Err(err) => {
+            eprintln!("{}\n\n{}", err, USAGE);
+            std::process::exit(2);
+        }
+    };
+
+    bevy_bird_lib::start_game(config);
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+fn config() -> GameConfig {
+    GameConfig::new().window_size(1280., 720.)
+}
+
+// Leaves room on the page for the title and the instructions around the canvas
+#[cfg(target_arch = "wasm32")]
+fn config() -> GameConfig {
+    GameConfig::new().window_size(960., 540.)
+}
+
+const USAGE: &str = "\
+Usage: bevy_bird [OPTIONS]
+
+Options:
+  --seed <SEED>          Fly over the hills from this seed
+  --size <WIDTHxHEIGHT>  Size of the window, like 1280x720
+  --fullscreen           Cover the whole screen
+  --no-vsync             Don't wait for the display to show frames
+  --replay <FILE>        Play back a replay file
+  --headless             Simulate the run without a window and print the result
+  --skin <NAME>          Start with this skin
+  --help                 Print this help";
+
+fn parse_args(
+    mut config: GameConfig,
+    args: impl IntoIterator<Item = String>,
+) -> Result<GameConfig, String> {
+    let mut args = args.into_iter();
+
+    while let Some(arg) = args.next() {
+        let mut value = || args.next().ok_or_else(|| format!("{} needs a value", arg));
+
+        config = match arg.as_str() {
+            "--seed" => {
+                let seed = value()?;
+                config.seed(
+                    seed.parse()
+                        .map_err(|_| format!("{} is not a valid seed", seed))?,
+                )
+            }
+            "--size" => {
+                let size = value()?;
+                let (width, height) =
+                    parse_size(&size).ok_or_else(|| format!("{} is not a valid size", size))?;
+                config.window_size(width, height)
+            }
+            "--fullscreen" => config.fullscreen(true),
+            "--no-vsync" => config.vsync(false),
+            "--replay" => config.replay(value()?),
+            "--headless" => config.headless(true),
+            "--skin" => config.skin(value()?),
+            "--help" => {
+                println!("{}", USAGE);
+                std::process::exit(0);
+            }
+            _ => return Err(format!("unknown option {}", arg)),
+        };
+    }
+
+    Ok(config)
+}
+
+fn parse_size(size: &str) -> Option<(f32, f32)> {
+    let (width, height) = size.split_once('x')?;
+
+    Some((width.parse().ok()?, height.parse().ok()?))
 }